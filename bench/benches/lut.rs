@@ -0,0 +1,105 @@
+/*
+Compares the table based u8 kernels with the same conversions computed per pixel, which
+is what the kernels did before the tables. Run the output side with the table enabled:
+
+    cargo bench -p colorspaces-bench --features gamma-lut
+*/
+
+use colorspaces::*;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const NUM_PIXELS: usize = 256 * 256;
+
+const D50_2: (f32, f32, f32) = (0.34567, 0.35850, 1.0);
+
+fn test_image() -> Vec<u8> {
+    (0..NUM_PIXELS).flat_map(|i| vec![(i % 256) as u8, (i / 256) as u8, (i * 7 % 256) as u8, 255]).collect()
+}
+
+fn encode_u8(value: f32) -> u8 {
+    (f32::to_bits(value.clamp(0.0, 255.0) + 256.5) >> 15) as u8
+}
+
+fn srgb_to_linear_rgb_per_pixel(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let srgb = SRGBColor(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0);
+        let LinearRGBColor(r, g, b) = srgb_to_linear_rgb(&srgb);
+        pixel[0] = encode_u8(r * 255.0);
+        pixel[1] = encode_u8(g * 255.0);
+        pixel[2] = encode_u8(b * 255.0);
+    }
+}
+
+fn srgb_to_lab_per_pixel(data: &mut [u8], white: &CIEXYZColor) {
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, white, ChromaticAdaptationMethod::default());
+    for pixel in data.chunks_exact_mut(4) {
+        let srgb = SRGBColor(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0);
+        let CIELabColor(l, a, b) = xyz_to_lab(&adaptation.apply(&srgb_to_xyz(&srgb)), white);
+        pixel[0] = encode_u8(l * 2.55);
+        pixel[1] = encode_u8((a + 106.0) * 1.1383928);
+        pixel[2] = encode_u8((b + 128.0) * 1.0493827);
+    }
+}
+
+fn linear_rgb_to_srgb_per_pixel(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let lin_rgb = LinearRGBColor(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0);
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&lin_rgb);
+        pixel[0] = encode_u8(r * 255.0);
+        pixel[1] = encode_u8(g * 255.0);
+        pixel[2] = encode_u8(b * 255.0);
+    }
+}
+
+fn bench_linearization(c: &mut Criterion) {
+    let image = test_image();
+    let (x, y, yy) = D50_2;
+    let white = CIEXYZColor(x / y * yy, yy, (1.0 - x - y) / y * yy);
+
+    let mut group = c.benchmark_group("srgb_input");
+    group.throughput(Throughput::Elements(NUM_PIXELS as u64));
+
+    group.bench_function("srgb_to_linear_rgb/table", |bench| bench.iter_batched_ref(
+        || image.clone(),
+        |data| convert_srgb_to_linear_rgb(data),
+        criterion::BatchSize::LargeInput
+    ));
+    group.bench_function("srgb_to_linear_rgb/polynomial", |bench| bench.iter_batched_ref(
+        || image.clone(),
+        |data| srgb_to_linear_rgb_per_pixel(data),
+        criterion::BatchSize::LargeInput
+    ));
+    group.bench_function("srgb_to_lab/table", |bench| bench.iter_batched_ref(
+        || image.clone(),
+        |data| convert_srgb_to_lab(data, x, y, yy, None),
+        criterion::BatchSize::LargeInput
+    ));
+    group.bench_function("srgb_to_lab/polynomial", |bench| bench.iter_batched_ref(
+        || image.clone(),
+        |data| srgb_to_lab_per_pixel(data, &white),
+        criterion::BatchSize::LargeInput
+    ));
+    group.finish();
+}
+
+fn bench_gamma(c: &mut Criterion) {
+    let image = test_image();
+
+    let mut group = c.benchmark_group("srgb_output");
+    group.throughput(Throughput::Elements(NUM_PIXELS as u64));
+
+    group.bench_function("linear_rgb_to_srgb/kernel", |bench| bench.iter_batched_ref(
+        || image.clone(),
+        |data| convert_linear_rgb_to_srgb(data),
+        criterion::BatchSize::LargeInput
+    ));
+    group.bench_function("linear_rgb_to_srgb/polynomial", |bench| bench.iter_batched_ref(
+        || image.clone(),
+        |data| linear_rgb_to_srgb_per_pixel(data),
+        criterion::BatchSize::LargeInput
+    ));
+    group.finish();
+}
+
+criterion_group!(benches, bench_linearization, bench_gamma);
+criterion_main!(benches);
@@ -18,7 +18,9 @@ p5.prototype._cs_inputMaxes = {
   [constants.CIELAB]: [1.0, 1.0, 1.0, 1.0],
   [constants.CIELUV]: [1.0, 1.0, 1.0, 1.0],
   [constants.CIELCH]: [1.0, 1.0, 1.0, 1.0],
-  [constants.CIELCHUV]: [1.0, 1.0, 1.0, 1.0]
+  [constants.CIELCHUV]: [1.0, 1.0, 1.0, 1.0],
+  [constants.OKLAB]: [1.0, 1.0, 1.0, 1.0],
+  [constants.OKLCH]: [1.0, 1.0, 1.0, 1.0]
 };
 
 p5.prototype._cs_originalColorMode = p5.prototype.colorMode;
@@ -81,6 +83,8 @@ function createGrayscaleTristimulus(gray, colorSpace, white) {
     case constants.CIELCH:
     case constants.CIELUV:
     case constants.CIELCHUV:
+    case constants.OKLAB:
+    case constants.OKLCH:
       return [gray, 0.0, 0.0, 1.0];
   }
 }
@@ -147,6 +151,7 @@ p5.prototype.color = function (...args) {
     inputWhite,
     this._cs_mixingColorSpace,
     this._cs_mixingWhitePoint,
+    this._cs_gamutMappingMethod(),
     this._cs_backend
   );
   const outColorScaled = applyScaling(outColor, this._cs_mixingColorSpace);
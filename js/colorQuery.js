@@ -87,6 +87,7 @@ p5.prototype._cs_ensureP5ColorWithSRGB = function (colorArgs, backend) {
       sourceWhitePoint,
       constants.SRGB,
       D65_2,
+      this._cs_gamutMappingMethod(),
       backend
     );
   }
@@ -1,6 +1,40 @@
 import { D65_2 } from "./whitepoints.js";
 import * as constants from "./constants.js";
 
+/*
+XYZ coordinates of sRGB white. All CIEXYZ colors passed between colorSpaceToXYZ and
+XYZToColorSpace are relative to this white.
+*/
+const HUB_WHITE_XYZ = [0.9504559270516716, 1.0, 1.0890577507598784];
+
+/*
+Returns true if the color space is defined relative to a user supplied white point.
+*/
+function usesWhitePoint(colorSpace) {
+  return colorSpace === constants.CIELAB || colorSpace === constants.CIELCH ||
+    colorSpace === constants.CIELUV || colorSpace === constants.CIELCHUV;
+}
+
+/*
+Applies a chromatic adaptation transform to a CIEXYZ color.
+
+@param xyzColor input CIEXYZ color.
+@param sourceWhiteXYZ CIEXYZ white the input is relative to.
+@param targetWhiteXYZ CIEXYZ white the output should be relative to.
+@param backend WASM backend.
+*/
+function adaptXYZ(xyzColor, sourceWhiteXYZ, targetWhiteXYZ, backend) {
+  let adaptation;
+  try {
+    adaptation = new backend.ChromaticAdaptation(sourceWhiteXYZ, targetWhiteXYZ, backend.ChromaticAdaptationMethod.Bradford);
+    return adaptation.apply(xyzColor);
+  } finally {
+    if (adaptation) {
+      adaptation.free();
+    }
+  }
+}
+
 /*
 Converts a color tuple into the CIEXYZ color space.
 
@@ -12,6 +46,7 @@ Converts a color tuple into the CIEXYZ color space.
 function colorSpaceToXYZ(input, sourceColorSpace, sourceWhite, backend) {
   let inputWASM;
   let sourceWhiteXYZ;
+  let hubWhiteXYZ;
   try{
     const [chromaX, chromaY, y] = sourceWhite;
     sourceWhiteXYZ = new backend.CIEXYZColor(
@@ -37,14 +72,40 @@ function colorSpaceToXYZ(input, sourceColorSpace, sourceWhite, backend) {
         inputWASM = new backend.CIELabColor(input[0], input[1], input[2]);
         out = backend.lab_to_xyz(inputWASM, sourceWhiteXYZ);
         break;
+      case constants.CIELCH:
+        inputWASM = new backend.CIELChColor(input[0], input[1], input[2]);
+        out = backend.lch_to_xyz(inputWASM, sourceWhiteXYZ);
+        break;
       case constants.CIELUV:
         inputWASM = new backend.CIELuvColor(input[0], input[1], input[2]);
         out = backend.luv_to_xyz(inputWASM, sourceWhiteXYZ);
         break;
+      case constants.CIELCHUV:
+        inputWASM = new backend.CIELChuvColor(input[0], input[1], input[2]);
+        out = backend.lchuv_to_xyz(inputWASM, sourceWhiteXYZ);
+        break;
+      case constants.OKLAB:
+        inputWASM = new backend.OklabColor(input[0], input[1], input[2]);
+        out = backend.oklab_to_xyz(inputWASM);
+        break;
+      case constants.OKLCH:
+        inputWASM = new backend.OklchColor(input[0], input[1], input[2]);
+        out = backend.oklch_to_xyz(inputWASM);
+        break;
+    }
+
+    if (usesWhitePoint(sourceColorSpace)) {
+      hubWhiteXYZ = new backend.CIEXYZColor(...HUB_WHITE_XYZ);
+      const unadapted = out;
+      out = adaptXYZ(unadapted, sourceWhiteXYZ, hubWhiteXYZ, backend);
+      unadapted.free();
     }
 
     return out;
   } finally {
+    if (hubWhiteXYZ) {
+      hubWhiteXYZ.free();
+    }
     if (sourceWhiteXYZ) {
       sourceWhiteXYZ.free();
     }
@@ -60,10 +121,13 @@ Converts a CIEXYZ color into another color space.
 @param xyzColor input CIEXYZ color.
 @param targetColorSpace Color space to convert the input color to.
 @param targetWhite CIExyY color to be used as a reference white point, if required.
+@param gamutMapping GamutMappingMethod of the backend, used for sRGB targets.
 @param backend WASM backend.
 */
-function XYZToColorSpace(xyzColor, targetColorSpace, targetWhite, backend) {
+function XYZToColorSpace(xyzColor, targetColorSpace, targetWhite, gamutMapping, backend) {
   let targetWhiteXYZ;
+  let hubWhiteXYZ;
+  let adaptedXYZ;
   let outWasm;
   try {
     const [chromaX, chromaY, y] = targetWhite;
@@ -73,9 +137,15 @@ function XYZToColorSpace(xyzColor, targetColorSpace, targetWhite, backend) {
       (1.0 - chromaX - chromaY) / chromaY * y
     );
 
+    if (usesWhitePoint(targetColorSpace)) {
+      hubWhiteXYZ = new backend.CIEXYZColor(...HUB_WHITE_XYZ);
+      adaptedXYZ = adaptXYZ(xyzColor, hubWhiteXYZ, targetWhiteXYZ, backend);
+      xyzColor = adaptedXYZ;
+    }
+
     switch (targetColorSpace) {
       case constants.SRGB:
-        outWasm = backend.xyz_to_srgb(xyzColor);
+        outWasm = backend.gamut_map_xyz_to_srgb(xyzColor, gamutMapping);
         break;
       case constants.LINEAR_RGB:
         outWasm = backend.xyz_to_linear_rgb(xyzColor);
@@ -88,9 +158,21 @@ function XYZToColorSpace(xyzColor, targetColorSpace, targetWhite, backend) {
       case constants.CIELAB:
         outWasm = backend.xyz_to_lab(xyzColor, targetWhiteXYZ);
         break;
+      case constants.CIELCH:
+        outWasm = backend.xyz_to_lch(xyzColor, targetWhiteXYZ);
+        break;
       case constants.CIELUV:
         outWasm = backend.xyz_to_luv(xyzColor, targetWhiteXYZ);
         break;
+      case constants.CIELCHUV:
+        outWasm = backend.xyz_to_lchuv(xyzColor, targetWhiteXYZ);
+        break;
+      case constants.OKLAB:
+        outWasm = backend.xyz_to_oklab(xyzColor);
+        break;
+      case constants.OKLCH:
+        outWasm = backend.xyz_to_oklch(xyzColor);
+        break;
     }
    
     return [outWasm[0], outWasm[1], outWasm[2]];
@@ -98,6 +180,12 @@ function XYZToColorSpace(xyzColor, targetColorSpace, targetWhite, backend) {
     if (outWasm) {
       outWasm.free();
     }
+    if (adaptedXYZ) {
+      adaptedXYZ.free();
+    }
+    if (hubWhiteXYZ) {
+      hubWhiteXYZ.free();
+    }
     if (targetWhiteXYZ) {
       targetWhiteXYZ.free();
     }
@@ -117,8 +205,16 @@ export function unapplyScaling(input, colorSpace) {
       return [input[0] * 0.95047, input[1], input[2] * 1.08883, input[3]];
     case constants.CIELAB:
       return [input[0] * 100.0, (input[1] * 224) - 106, (input[2] * 243) - 128, input[3]];
+    case constants.CIELCH:
+      return [input[0] * 100.0, input[1] * 134, input[2] * 360, input[3]];
     case constants.CIELUV:
       return [input[0] * 100.0, (input[1] * 298) - 103, (input[2] * 282) - 154, input[3]];
+    case constants.CIELCHUV:
+      return [input[0] * 100.0, input[1] * 180, input[2] * 360, input[3]];
+    case constants.OKLAB:
+      return [input[0], (input[1] * 0.52) - 0.24, (input[2] * 0.52) - 0.32, input[3]];
+    case constants.OKLCH:
+      return [input[0], input[1] * 0.33, input[2] * 360, input[3]];
   }
 }
 
@@ -135,17 +231,25 @@ export function applyScaling(input, colorSpace) {
       return [input[0] / 0.95047, input[1], input[2] / 1.08883, input[3]];
     case constants.CIELAB:
       return [input[0] / 100.0, (input[1] + 106) / 224, (input[2] + 128) / 243, input[3]];
+    case constants.CIELCH:
+      return [input[0] / 100.0, input[1] / 134, input[2] / 360, input[3]];
     case constants.CIELUV:
       return [input[0] / 100.0, (input[1] + 103) / 298, (input[2] + 154) / 282, input[3]];
+    case constants.CIELCHUV:
+      return [input[0] / 100.0, input[1] / 180, input[2] / 360, input[3]];
+    case constants.OKLAB:
+      return [input[0], (input[1] + 0.24) / 0.52, (input[2] + 0.32) / 0.52, input[3]];
+    case constants.OKLCH:
+      return [input[0], input[1] / 0.33, input[2] / 360, input[3]];
   }
 }
 
-export function convertColor(color, sourceColorSpace, sourceWhitePoint, targetColorSpace, targetWhitePoint, backend) {
+export function convertColor(color, sourceColorSpace, sourceWhitePoint, targetColorSpace, targetWhitePoint, gamutMapping, backend) {
   let xyzColor;
 
   try {
     xyzColor = colorSpaceToXYZ(color, sourceColorSpace, sourceWhitePoint, backend);
-    const outColor = XYZToColorSpace(xyzColor, targetColorSpace, targetWhitePoint, backend);
+    const outColor = XYZToColorSpace(xyzColor, targetColorSpace, targetWhitePoint, gamutMapping, backend);
 
     return [outColor[0], outColor[1], outColor[2], color[3]];
   } finally {
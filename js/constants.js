@@ -1,7 +1,4 @@
 export const MSG_WASM_MODULE = 0;
-export const MSG_CONVERSION = 1;
-export const MSG_SEMAPHORE = 2;
-export const MSG_ACK_WASM_MODULE = 4;
 
 export const SRGB = "sRGB";
 export const LINEAR_RGB = "linear RGB";
@@ -9,4 +6,10 @@ export const CIEXYZ = "CIEXYZ";
 export const CIELAB = "CIELab";
 export const CIELCH = "CIELCh";
 export const CIELUV = "CIELuv";
-export const CIELCHUV = "CIELChuv";
\ No newline at end of file
+export const CIELCHUV = "CIELChuv";
+export const OKLAB = "Oklab";
+export const OKLCH = "Oklch";
+
+export const GAMUT_CLIP = "clip";
+export const GAMUT_OKLCH = "Oklch chroma reduction";
+export const GAMUT_MINDE = "MINDE";
@@ -43,6 +43,21 @@ class ColorSpacesError extends Error {
   }
 }
 
+/*
+Calls into the backend. The backend reports invalid arguments, such as an unknown color space
+or an impossible white point, as errors named "ColorSpacesError", which are rethrown as ColorSpacesError.
+*/
+function callBackend(f) {
+  try {
+    return f();
+  } catch (e) {
+    if (e instanceof Error && e.name == "ColorSpacesError") {
+      throw new ColorSpacesError(e.message);
+    }
+    throw e;
+  }
+}
+
 /*
 Functions for loading the WASM backend
 */
@@ -52,40 +67,15 @@ p5.prototype._cs_wasmInstance = null;
 p5.prototype._cs_backendLoaded = false;
 p5.prototype._cs_wasmMemory = null;
 p5.prototype._cs_threads = [];
-p5.prototype._cs_semaphore = null;
 
 /*
-Initializes a worker thread by sending it the WASM module and memory.
-
-When a worker thread successfully instatiated a module, it responds with
-a MSG_ACK_WASM_MODULE message. This function exists to make the callback 
-from this message await-able.
-*/
-function initWorker(worker, wasmModule, wasmMemory) {
-  return new Promise(function(resolve, reject) {
-    worker.onmessage = function (e) {
-      if (e.data.id == constants.MSG_ACK_WASM_MODULE) {
-        resolve();
-      } else {
-        reject();
-      }
-    };
-    worker.postMessage({
-      id: constants.MSG_WASM_MODULE,
-      module: wasmModule,
-      memory: wasmMemory
-    })
-  });
-}
-
-/*
-Initializes the library. This function loads the WASM backend as well as setup up
-the worker threads.
+Initializes the library. This function loads the WASM backend and starts the backend's
+thread pool.
 */
 p5.prototype.loadColorSpaces = async function() {
   const wasmURL = new URL("colorspaces_bg.wasm", import.meta.url);
 
-  // Prepare compiled module and memory so that they can be sent to the worker threads.
+  // Prepare compiled module and memory so that they can be shared with the worker threads.
   const wasmModule = await WebAssembly.compileStreaming(fetch(wasmURL));
   // TODO: dynamically figure out memory limits
   const wasmMemory = new WebAssembly.Memory({initial: 17, maximum: 16384, shared: true});
@@ -96,26 +86,23 @@ p5.prototype.loadColorSpaces = async function() {
   this._cs_backend = {};
   Object.assign(this._cs_backend, backend);
 
-  // Setup web workers
-  // -1 because the main thread will also keep running
+  // The backend asks for one web worker per thread of its pool and hands each of them
+  // a thread to run. -1 because the main thread also converts while it waits for the pool.
   const numWorkers = navigator.hardwareConcurrency - 1;
   const workerURL = new URL("worker.js", import.meta.url);
-  for (let i = 0; i < numWorkers; i++) {
-    const worker = new Worker(workerURL, {name: "thread " + i});
-    await initWorker(worker, wasmModule, wasmMemory);
-    this._cs_threads.push(worker);
-  }
-
-  // Setup a tiny shared buffer to serve as a semaphore. This is used
-  // later to synchronize the main thread and the worker threads.
-  const semaphoreBuffer = new SharedArrayBuffer(4);
-  for (let w of this._cs_threads) {
-    w.postMessage({
-      id: constants.MSG_SEMAPHORE,
-      semaphoreBuffer: semaphoreBuffer
+  const threads = this._cs_threads;
+  if (numWorkers > 0) {
+    backend.init_thread_pool(numWorkers, function(module, memory, thread) {
+      const worker = new Worker(workerURL, {name: "thread " + threads.length});
+      worker.postMessage({
+        id: constants.MSG_WASM_MODULE,
+        module: module,
+        memory: memory,
+        thread: thread
+      });
+      threads.push(worker);
     });
   }
-  this._cs_semaphore = new Int32Array(semaphoreBuffer);
 
   // Mark the backend as loaded
   this._cs_backendLoaded = true;
@@ -147,6 +134,12 @@ p5.prototype.CIELAB = constants.CIELAB;
 p5.prototype.CIELCH = constants.CIELCH;
 p5.prototype.CIELUV = constants.CIELUV;
 p5.prototype.CIELCHUV = constants.CIELCHUV;
+p5.prototype.OKLAB = constants.OKLAB;
+p5.prototype.OKLCH = constants.OKLCH;
+
+p5.prototype.GAMUT_CLIP = constants.GAMUT_CLIP;
+p5.prototype.GAMUT_OKLCH = constants.GAMUT_OKLCH;
+p5.prototype.GAMUT_MINDE = constants.GAMUT_MINDE;
 
 Object.assign(p5.prototype, white);
 
@@ -156,96 +149,126 @@ Conversion functions
 
 p5.prototype._cs_mixingColorSpace = constants.SRGB;
 p5.prototype._cs_mixingWhitePoint = white.D65_2;
+p5.prototype._cs_gamutMapping = constants.GAMUT_CLIP;
+p5.prototype._cs_accurateConversions = false;
 
-p5.prototype._cs_allocationPtr = 0;
-p5.prototype._cs_allocationLen = 0;
+p5.prototype._cs_buffer = null;
 
 /*
-Ensures that the current memory allocation for the backend has at least the given length.
+Ensures that the backend's buffer holds at least the given number of bytes.
 
-p5.colorSpaces allocations a section in WASM memory which is used to hold
-the data to be converted between color spaces. On the JS side, this allocation is represented by
-a pointer (_cs_allocationPtr) and a length (_cs_allocationLen), which are supplied to the
-conversion functions.
+p5.colorSpaces keeps a ColorBuffer in WASM memory which holds the data to be converted between
+color spaces. The buffer is owned by the backend, which checks every region that is converted
+against it.
 */
-p5.prototype._cs_ensureAllocationSize = function (len) {
-  if (this._cs_allocationLen < len) {
-    // If we need to re-allocate, free the previous allocation first.
-    if (this._cs_allocationPtr != 0) {
-      this._cs_backend.deallocate_buffer(this._cs_allocationPtr, this._cs_allocationLen);
-    }
-
-    // Allocate new memory
-    this._cs_allocationPtr = this._cs_backend.allocate_buffer(len);
-    this._cs_allocationLen = len;
+p5.prototype._cs_ensureBufferSize = function (len) {
+  if (this._cs_buffer === null) {
+    this._cs_buffer = new this._cs_backend.ColorBuffer(len);
+  } else if (this._cs_buffer.len() < len) {
+    this._cs_buffer.resize(len);
   }
 }
 
 /*
-Frees the current WASM memory allocation. This function is used to clean up when the
-p5.js sketch is removed.
+Frees the backend's buffer. This function is used to clean up when the p5.js sketch is removed.
 */
-p5.prototype._cs_deallocate = function () {
-  if (this._cs_allocation) {
-    this._cs_backend.deallocate_buffer(this._cs_allocationPtr, this._cs_allocation_size);
+p5.prototype._cs_freeBuffer = function () {
+  if (this._cs_buffer !== null) {
+    this._cs_buffer.free();
+    this._cs_buffer = null;
   }
 }
 
-p5.prototype.registerMethod("remove", p5.prototype._cs_deallocate);
+p5.prototype.registerMethod("remove", p5.prototype._cs_freeBuffer);
 
 /*
-Applies a conversion function to an ImageData object.
+Applies a conversion to an ImageData object.
 
-This function uses both the main thread and the worker threads to convert between color spaces.
+pipelineArgs are the arguments of the backend's ConversionPipeline constructor.
+
+The backend splits the conversion between the main thread and its worker threads and
+returns when all of the image is converted.
 */
-p5.prototype._cs_convertImageData = function (imageData, conversionFunc, whitePoint) {
+p5.prototype._cs_convertImageData = function (imageData, pipelineArgs) {
   const imageArray = new Uint8Array(imageData.data.buffer);
   const dataLength = imageArray.length;
-  this._cs_ensureAllocationSize(dataLength);
-
-  const wasmMemoryView = this._cs_backend.get_memory_view(this._cs_allocationPtr, this._cs_allocationLen);
-  wasmMemoryView.set(imageArray);
-
-  const numPixels = dataLength >> 2;
-  const numWorkers = this._cs_threads.length;
-  // pixelsPerThread takes the main thread into account which also does computation
-  const pixelsPerThread = Math.floor(numPixels / (numWorkers + 1));
-
-  // Prepare semaphore. Every time a worker thread has finished, it decrements this values.
-  // After the main thread has processed it's part of the input data, it waits for this
-  // value to become zero to exit the function. Kinda hacky, but it does make
-  // a usually asynchronous process synchronous.
-  Atomics.store(this._cs_semaphore, 0, numWorkers);
-
-  // Start worker threads
-  for (let i = 0; i < numWorkers; i++) {
-    this._cs_threads[i].postMessage({
-      id: constants.MSG_CONVERSION,
-      func: conversionFunc,
-      ptr: this._cs_allocationPtr,
-      offset: i * pixelsPerThread * 4,
-      whitePoint: whitePoint,
-      len: pixelsPerThread * 4
-    });
-  }
 
-  // To the last part of the conversion on the main thread.
-  const mainThreadOffset = numWorkers * pixelsPerThread * 4;
-  const mainThreadLen = dataLength - mainThreadOffset;
-  this._cs_backend[conversionFunc](this._cs_allocationPtr, mainThreadOffset, mainThreadLen, ...whitePoint);
+  callBackend(() => {
+    const pipeline = new this._cs_backend.ConversionPipeline(...pipelineArgs);
+    try {
+      this._cs_ensureBufferSize(dataLength);
 
-  // Wait for worker threads to finish
-  while(true) {
-    if (Atomics.load(this._cs_semaphore, 0) <= 0) {
-      break;
+      // Views of the buffer are invalidated by allocations, so they are not kept across calls into the backend.
+      this._cs_buffer.view().set(imageArray);
+      pipeline.convert_buffer_parallel(this._cs_buffer, 0, dataLength);
+    } finally {
+      pipeline.free();
     }
+  });
+
+  imageArray.set(this._cs_buffer.view().subarray(0, dataLength));
+}
+
+/*
+Names of the backend's GamutMappingMethod variants for each gamut mapping constant.
+*/
+const gamutMappingMethods = {
+  [constants.GAMUT_CLIP]: "Clip",
+  [constants.GAMUT_OKLCH]: "OklchChromaReduction",
+  [constants.GAMUT_MINDE]: "MinDE"
+};
+
+/*
+The backend's GamutMappingMethod for the method set with gamutMapping().
+*/
+p5.prototype._cs_gamutMappingMethod = function () {
+  return this._cs_backend.GamutMappingMethod[gamutMappingMethods[this._cs_gamutMapping]];
+}
+
+/*
+Builds the ConversionPipeline constructor arguments for a conversion between two color spaces.
+The white points are CIExyY triples, they are ignored by color spaces that do not need one.
+*/
+p5.prototype._cs_pipelineArgs = function (sourceColorSpace, sourceWhitePoint, targetColorSpace, targetWhitePoint) {
+  const backend = this._cs_backend;
+  const gamutMapping = this._cs_gamutMappingMethod();
+  // The backend knows the names of the color space constants.
+  const [sourceId, targetId] = callBackend(() => [
+    backend.parse_color_space(String(sourceColorSpace)),
+    backend.parse_color_space(String(targetColorSpace))
+  ]);
+  // undefined selects the default chromatic adaptation transform.
+  return [
+    sourceId, ...sourceWhitePoint,
+    targetId, ...targetWhitePoint,
+    undefined, gamutMapping, this._cs_accurateConversions
+  ];
+}
+
+/*
+Sets how out-of-gamut colors are brought back into sRGB by exitColorSpace() and by
+conversions of single colors into sRGB.
+*/
+p5.prototype.gamutMapping = function(method) {
+  if (!(method in gamutMappingMethods)) {
+    throw new ColorSpacesError("Unknown gamut mapping method: " + method);
   }
+  this._cs_gamutMapping = method;
+}
 
-  imageArray.set(wasmMemoryView);
+/*
+Computes the transfer functions of the color spaces exactly instead of approximating them.
+This is slower, but gives reference results.
+*/
+p5.prototype.accurateConversions = function(enabled) {
+  this._cs_accurateConversions = !!enabled;
 }
 
 /*
 Converts the whole canvas into a given color space.
+
+If the canvas already is in another color space, it is converted directly from that
+color space in a single pass, without going through sRGB.
 */
 p5.prototype.enterColorSpace = function(colorSpace, whitePoint) {
   this._cs_checkIfBackendLoaded();
@@ -254,27 +277,17 @@ p5.prototype.enterColorSpace = function(colorSpace, whitePoint) {
     whitePoint = white.D65_2;
   }
 
-  let conversionFunc;
-  switch (colorSpace) {
-    case constants.SRGB:
-      return;
-    case constants.CIEXYZ:
-      conversionFunc = "convert_memory_srgb_to_xyz";
-      break;
-    case constants.LINEAR_RGB:
-      conversionFunc = "convert_memory_srgb_to_linear_rgb";
-      break;
-    case constants.CIELAB:
-      conversionFunc = "convert_memory_srgb_to_lab";
-      break;
-    case constants.CIELUV:
-      conversionFunc = "convert_memory_srgb_to_luv";
-      break;
+  const sourceColorSpace = this._cs_mixingColorSpace;
+  const sourceWhitePoint = this._cs_mixingWhitePoint;
+  if (colorSpace == sourceColorSpace && whitePoint.every((v, i) => v == sourceWhitePoint[i])) {
+    return;
   }
 
+  const pipelineArgs = this._cs_pipelineArgs(sourceColorSpace, sourceWhitePoint, colorSpace, whitePoint);
+
   const imageData = this.drawingContext.getImageData(0, 0, this.width, this.height);
   
-  this._cs_convertImageData(imageData, conversionFunc, whitePoint);
+  this._cs_convertImageData(imageData, pipelineArgs);
 
   this.drawingContext.putImageData(imageData, 0, 0);
 
@@ -293,27 +306,15 @@ p5.prototype.exitColorSpace = function() {
   //   return;
   // }
 
-  let conversionFunc;
-  switch (this._cs_mixingColorSpace) {
-    case constants.SRGB:
-      return;
-    case constants.CIEXYZ:
-      conversionFunc = "convert_memory_xyz_to_srgb";
-      break;
-    case constants.LINEAR_RGB:
-      conversionFunc = "convert_memory_linear_rgb_to_srgb";
-      break;
-    case constants.CIELAB:
-      conversionFunc = "convert_memory_lab_to_srgb";
-      break;
-    case constants.CIELUV:
-      conversionFunc = "convert_memory_luv_to_srgb";
-      break;
+  if (this._cs_mixingColorSpace == constants.SRGB) {
+    return;
   }
 
+  const pipelineArgs = this._cs_pipelineArgs(this._cs_mixingColorSpace, this._cs_mixingWhitePoint, constants.SRGB, white.D65_2);
+
   const imageData = this.drawingContext.getImageData(0, 0, this.width, this.height);
   
-  this._cs_convertImageData(imageData, conversionFunc, this._cs_mixingWhitePoint);
+  this._cs_convertImageData(imageData, pipelineArgs);
 
   this.drawingContext.putImageData(imageData, 0, 0);
 
@@ -3,29 +3,12 @@
 import init, * as backend from "../pkg/colorspaces.js";
 import * as constants from "./constants.js";
 
-let semaphore = null;
-
-function semaphoreDown(s) {
-  Atomics.sub(s, 0, 1);
-}
-
 onmessage = function(e) {
   if (e.data.id == constants.MSG_WASM_MODULE) {
-    // Message contains a WASM module.
+    // Message contains the WASM module, the shared memory and the handle of a thread of the backend's
+    // thread pool. The worker becomes that thread and keeps running it until the page is closed.
     init(e.data.module, e.data.memory).then(() => {
-      postMessage({id: constants.MSG_ACK_WASM_MODULE});
+      backend.run_pool_worker(e.data.thread);
     });
-  } else if (e.data.id == constants.MSG_CONVERSION) {
-    // Message contains a section of WASM memory to convert, and a conversion function to use.
-    const func = e.data.func;
-    const ptr = e.data.ptr;
-    const offset = e.data.offset;
-    const len = e.data.len;
-    const whitePoint = e.data.whitePoint;
-    backend[func](ptr, offset, len, ...whitePoint);
-    semaphoreDown(semaphore);
-  } else if (e.data.id == constants.MSG_SEMAPHORE) {
-    // Message contains a semaphore.
-    semaphore = new Int32Array(e.data.semaphoreBuffer);
   }
 }
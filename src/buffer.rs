@@ -0,0 +1,191 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "wasm")]
+use js_sys::{Uint8Array, Uint16Array, Float32Array};
+
+use crate::error::{ColorSpacesError, check_pixels};
+
+/*
+Buffers of RGBA pixels owned by the crate, one type for each kind of component.
+
+JavaScript writes pixels into a buffer through view(), converts regions of it with the
+convert_memory_* functions or ConversionPipeline::convert_buffer(), and releases it with free().
+Regions are given as offset and length in components, i.e. bytes for ColorBuffer. They are checked
+against the buffer, so a wrong offset results in an error instead of writing to arbitrary memory.
+wasm-bindgen does not support generic types, so the three types are generated by this macro.
+*/
+macro_rules! color_buffer {
+    ($name:ident, $component:ty, $view:ident) => {
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            data: Vec<$component>
+        }
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $name {
+
+            // The length is given in components and has to be a whole number of pixels.
+            #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+            pub fn new(len: usize) -> Result<$name, ColorSpacesError> {
+                check_pixels(0, len)?;
+                Ok($name { data: vec![Default::default(); len] })
+            }
+
+            pub fn len(&self) -> usize {
+                self.data.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.data.is_empty()
+            }
+
+            // Grows or shrinks the buffer. The contents are kept as far as they fit.
+            pub fn resize(&mut self, len: usize) -> Result<(), ColorSpacesError> {
+                check_pixels(0, len)?;
+                self.data.resize(len, Default::default());
+                Ok(())
+            }
+
+            /*
+            A view of the contents of the buffer. It becomes invalid when the buffer is resized or freed,
+            and when the WASM memory grows, which any allocation may cause. So the view should be used
+            right away and not be kept around.
+            */
+            #[cfg(feature = "wasm")]
+            pub fn view(&mut self) -> $view {
+                unsafe {
+                    $view::view_mut_raw(self.data.as_mut_ptr(), self.data.len())
+                }
+            }
+        }
+
+        impl $name {
+
+            pub fn as_slice(&self) -> &[$component] {
+                &self.data
+            }
+
+            pub fn as_mut_slice(&mut self) -> &mut [$component] {
+                &mut self.data
+            }
+
+            // The pixels in the region of len components starting at offset.
+            pub fn region(&mut self, offset: usize, len: usize) -> Result<&mut [$component], ColorSpacesError> {
+                region(&mut self.data, offset, len)
+            }
+        }
+    };
+}
+
+color_buffer!(ColorBuffer, u8, Uint8Array);
+color_buffer!(ColorBufferF32, f32, Float32Array);
+color_buffer!(ColorBufferU16, u16, Uint16Array);
+
+impl ColorBuffer {
+
+    /*
+    A region to write into, together with regions to read from at the same time. The inputs may
+    overlap each other, but not the output. Only the bounds are checked, not the pixel alignment.
+    */
+    pub(crate) fn region_with_inputs<const N: usize>(&mut self, offset: usize, len: usize, inputs: [(usize, usize); N]) -> Result<(&mut [u8], [&[u8]; N]), ColorSpacesError> {
+        check_bounds(self.data.len(), offset, len)?;
+        for &(input_offset, input_len) in inputs.iter() {
+            check_bounds(self.data.len(), input_offset, input_len)?;
+            if len > 0 && input_len > 0 && input_offset < offset + len && offset < input_offset + input_len {
+                return Err(ColorSpacesError::OverlappingRegions { offset: input_offset, len: input_len, output_offset: offset, output_len: len });
+            }
+        }
+
+        let (before, rest) = self.data.split_at_mut(offset);
+        let (region, after) = rest.split_at_mut(len);
+        let (before, after): (&[u8], &[u8]) = (before, after);
+        let inputs = inputs.map(|(input_offset, input_len)| {
+            if input_len == 0 {
+                &before[..0]
+            } else if input_offset < offset {
+                &before[input_offset..input_offset + input_len]
+            } else {
+                &after[input_offset - offset - len..][..input_len]
+            }
+        });
+        Ok((region, inputs))
+    }
+}
+
+fn region<T>(data: &mut [T], offset: usize, len: usize) -> Result<&mut [T], ColorSpacesError> {
+    check_pixels(offset, len)?;
+    check_bounds(data.len(), offset, len)?;
+    Ok(&mut data[offset..offset + len])
+}
+
+fn check_bounds(buffer_len: usize, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    match offset.checked_add(len) {
+        Some(end) if end <= buffer_len => Ok(()),
+        _ => Err(ColorSpacesError::OutOfBounds { offset, len, buffer_len })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_color_buffer_regions() {
+        let mut buffer = ColorBuffer::new(64).unwrap();
+        buffer.as_mut_slice()[8] = 1;
+        assert_eq!(buffer.region(8, 16).unwrap()[0], 1);
+        assert_eq!(buffer.region(64, 0).unwrap().len(), 0);
+
+        assert!(ColorBuffer::new(10).is_err());
+        assert!(buffer.region(2, 8).is_err());
+        assert!(buffer.region(0, 6).is_err());
+        assert_eq!(buffer.region(60, 8).unwrap_err(), ColorSpacesError::OutOfBounds { offset: 60, len: 8, buffer_len: 64 });
+        assert!(buffer.region(usize::MAX - 3, 8).is_err());
+
+        buffer.resize(128).unwrap();
+        assert_eq!(buffer.len(), 128);
+        assert_eq!(buffer.as_slice()[8], 1);
+        assert!(buffer.resize(129).is_err());
+    }
+
+    #[test]
+    fn test_color_buffer_region_with_inputs() {
+        let mut buffer = ColorBuffer::new(64).unwrap();
+        for (i, v) in buffer.as_mut_slice().iter_mut().enumerate() {
+            *v = i as u8;
+        }
+
+        let (out, [a, b]) = buffer.region_with_inputs(16, 16, [(0, 16), (40, 8)]).unwrap();
+        assert_eq!(out[0], 16);
+        assert_eq!(a[15], 15);
+        assert_eq!(b, &[40, 41, 42, 43, 44, 45, 46, 47]);
+
+        // Inputs may overlap each other.
+        assert!(buffer.region_with_inputs(0, 8, [(8, 16), (12, 16)]).is_ok());
+
+        assert_eq!(buffer.region_with_inputs(16, 16, [(12, 8)]).unwrap_err(), ColorSpacesError::OverlappingRegions { offset: 12, len: 8, output_offset: 16, output_len: 16 });
+        assert!(buffer.region_with_inputs(16, 16, [(28, 8)]).is_err());
+        assert!(buffer.region_with_inputs(16, 16, [(60, 8)]).is_err());
+        assert!(buffer.region_with_inputs(60, 8, [(0, 4)]).is_err());
+    }
+
+    #[test]
+    fn test_color_buffer_f32_u16_regions() {
+        // Offsets and lengths are counted in components, not in bytes.
+        let mut buffer = ColorBufferF32::new(16).unwrap();
+        buffer.as_mut_slice()[4] = 0.5;
+        assert_eq!(buffer.region(4, 12).unwrap()[0], 0.5);
+        assert!(buffer.region(8, 12).is_err());
+        assert!(buffer.region(2, 4).is_err());
+        assert!(ColorBufferF32::new(6).is_err());
+
+        let mut buffer = ColorBufferU16::new(0).unwrap();
+        assert!(buffer.is_empty());
+        assert!(buffer.region(0, 4).is_err());
+        buffer.resize(8).unwrap();
+        assert_eq!(buffer.region(4, 4).unwrap(), &[0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::CIEXYZColor;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub type Matrix3 = [[f32; 3]; 3];
+
+pub(crate) const IDENTITY: Matrix3 = [
+    [1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, 0.0, 1.0]
+];
+
+/*
+XYZ of the white produced by srgb_to_xyz() for RGB (1, 1, 1), i.e. the row sums
+of the sRGB matrix. Using this instead of a rounded D65 ensures that sRGB white
+is adapted exactly onto the target white.
+*/
+pub const SRGB_WHITE: CIEXYZColor = CIEXYZColor(0.9504559, 1.0, 1.0890578);
+
+const VON_KRIES: Matrix3 = [
+    [0.40024, 0.70760, -0.08081],
+    [-0.22630, 1.16532, 0.04570],
+    [0.0, 0.0, 0.91822]
+];
+
+const BRADFORD: Matrix3 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296]
+];
+
+const CAT02: Matrix3 = [
+    [0.7328, 0.4296, -0.1624],
+    [-0.7036, 1.6975, 0.0061],
+    [0.0030, 0.0136, 0.9834]
+];
+
+const CAT16: Matrix3 = [
+    [0.401288, 0.650173, -0.051461],
+    [-0.250268, 1.204414, 0.045854],
+    [-0.002079, 0.048952, 0.953127]
+];
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ChromaticAdaptationMethod {
+    VonKries = 0,
+    #[default]
+    Bradford = 1,
+    CAT02 = 2,
+    CAT16 = 3
+}
+
+impl ChromaticAdaptationMethod {
+
+    fn cone_response_matrix(self) -> Matrix3 {
+        match self {
+            ChromaticAdaptationMethod::VonKries => VON_KRIES,
+            ChromaticAdaptationMethod::Bradford => BRADFORD,
+            ChromaticAdaptationMethod::CAT02 => CAT02,
+            ChromaticAdaptationMethod::CAT16 => CAT16
+        }
+    }
+}
+
+#[inline(always)]
+pub(crate) fn multiply_matrix_vector(m: &Matrix3, v: &CIEXYZColor) -> CIEXYZColor {
+    let &CIEXYZColor(x, y, z) = v;
+    CIEXYZColor(
+        m[0][0] * x + m[0][1] * y + m[0][2] * z,
+        m[1][0] * x + m[1][1] * y + m[1][2] * z,
+        m[2][0] * x + m[2][1] * y + m[2][2] * z
+    )
+}
+
+// const so that fixed products, like the XYZ matrices of Oklab, can be computed at compile time.
+pub(crate) const fn multiply_matrices(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut out = [[0.0; 3]; 3];
+    let mut i = 0;
+    while i < 3 {
+        let mut j = 0;
+        while j < 3 {
+            out[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+            j += 1;
+        }
+        i += 1;
+    }
+    out
+}
+
+pub(crate) fn invert_matrix(m: &Matrix3) -> Matrix3 {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+
+    let c00 = cofactor(1, 2, 1, 2);
+    let c01 = -cofactor(1, 2, 0, 2);
+    let c02 = cofactor(1, 2, 0, 1);
+    let det = m[0][0] * c00 + m[0][1] * c01 + m[0][2] * c02;
+
+    [
+        [c00 / det, -cofactor(0, 2, 1, 2) / det, cofactor(0, 1, 1, 2) / det],
+        [c01 / det, cofactor(0, 2, 0, 2) / det, -cofactor(0, 1, 0, 2) / det],
+        [c02 / det, -cofactor(0, 2, 0, 1) / det, cofactor(0, 1, 0, 1) / det]
+    ]
+}
+
+/*
+Linear transform which maps colors seen under a source white onto the colors
+that produce the same cone (or sharpened cone) response under a target white.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct ChromaticAdaptation {
+    matrix: Matrix3
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ChromaticAdaptation {
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(source_white: &CIEXYZColor, target_white: &CIEXYZColor, method: ChromaticAdaptationMethod) -> ChromaticAdaptation {
+        let cone_response = method.cone_response_matrix();
+        let CIEXYZColor(rho_s, gamma_s, beta_s) = multiply_matrix_vector(&cone_response, source_white);
+        let CIEXYZColor(rho_t, gamma_t, beta_t) = multiply_matrix_vector(&cone_response, target_white);
+
+        let scale = [
+            [rho_t / rho_s, 0.0, 0.0],
+            [0.0, gamma_t / gamma_s, 0.0],
+            [0.0, 0.0, beta_t / beta_s]
+        ];
+
+        let matrix = multiply_matrices(&invert_matrix(&cone_response), &multiply_matrices(&scale, &cone_response));
+        ChromaticAdaptation { matrix }
+    }
+
+    // Used by adaptXYZ() in js/colorUtils.js.
+    pub fn apply(&self, xyz: &CIEXYZColor) -> CIEXYZColor {
+        multiply_matrix_vector(&self.matrix, xyz)
+    }
+}
+
+impl ChromaticAdaptation {
+
+    pub(crate) fn matrix(&self) -> Matrix3 {
+        self.matrix
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn adapt_xyz(xyz: &CIEXYZColor, source_white: &CIEXYZColor, target_white: &CIEXYZColor, method: ChromaticAdaptationMethod) -> CIEXYZColor {
+    ChromaticAdaptation::new(source_white, target_white, method).apply(xyz)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::CompareMargin;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+    const D50: CIEXYZColor = CIEXYZColor(0.96422, 1.0, 0.82521);
+
+    #[test]
+    fn test_bradford_matrix() {
+        let expected = [
+            [1.0478112, 0.0228866, -0.0501270],
+            [0.0295424, 0.9904844, -0.0170491],
+            [-0.0092345, 0.0150436, 0.7521316]
+        ];
+        let matrix = ChromaticAdaptation::new(&D65, &D50, ChromaticAdaptationMethod::Bradford).matrix;
+        for (row, expected_row) in matrix.iter().zip(expected.iter()) {
+            for (val, expected_val) in row.iter().zip(expected_row.iter()) {
+                assert!((val - expected_val).abs() < 1.0e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_white_maps_to_white() {
+        for method in [ChromaticAdaptationMethod::VonKries, ChromaticAdaptationMethod::Bradford, ChromaticAdaptationMethod::CAT02, ChromaticAdaptationMethod::CAT16] {
+            assert!(adapt_xyz(&D65, &D65, &D50, method).equal_within(D50, MARGIN));
+            assert!(adapt_xyz(&D50, &D50, &D65, method).equal_within(D65, MARGIN));
+            assert!(adapt_xyz(&CIEXYZColor(0.3, 0.5, 0.8), &D65, &D65, method).equal_within(CIEXYZColor(0.3, 0.5, 0.8), MARGIN));
+        }
+    }
+
+    #[test]
+    fn test_adapt_xyz() {
+        assert!(adapt_xyz(&CIEXYZColor(0.4124, 0.2126, 0.0193), &D65, &D50, ChromaticAdaptationMethod::Bradford).equal_within(CIEXYZColor(0.4361, 0.2225, 0.0139), MARGIN));
+        assert!(adapt_xyz(&CIEXYZColor(0.4361, 0.2225, 0.0139), &D50, &D65, ChromaticAdaptationMethod::Bradford).equal_within(CIEXYZColor(0.4124, 0.2126, 0.0193), MARGIN));
+    }
+}
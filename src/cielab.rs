@@ -1,23 +1,27 @@
 use crate::{TristimulusColor, CIEXYZColor};
+use crate::color_space::ColorSpace;
+use crate::transfer::{TransferFunctions, DefaultTransfer};
+use crate::chromatic_adaptation::Matrix3;
 
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-const CORRECTION_FACTOR_3RD_ROOT: [f32; 5] = [
-    0.6299605249474366,
-    0.7937005259840998,
+pub(crate) const CORRECTION_FACTOR_3RD_ROOT: [f32; 5] = [
+    0.62996054,
+    0.7937005,
     1.0,
-    1.2599210498948732,
-    1.5874010519681994
+    1.2599211,
+    1.587401
 ];
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Copy, Clone)]
 pub struct CIELabColor(pub f32, pub f32, pub f32);
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl CIELabColor {
     
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(l: f32, a: f32, b: f32) -> CIELabColor {
         CIELabColor(l, a, b)
     }
@@ -38,52 +42,99 @@ impl TristimulusColor for CIELabColor {
     }
 }
 
-#[wasm_bindgen]
+/*
+The linear values of CIELab are the CIEXYZ components relative to the white point,
+so that the scaling by the white point can be fused with other matrices.
+*/
+const UNIT_WHITE: CIEXYZColor = CIEXYZColor(1.0, 1.0, 1.0);
+
+impl ColorSpace for CIELabColor {
+
+    const WHITE_RELATIVE: bool = true;
+
+    fn from_components(l: f32, a: f32, b: f32) -> CIELabColor {
+        CIELabColor(l, a, b)
+    }
+
+    fn to_linear(&self, _white: &CIEXYZColor) -> CIEXYZColor {
+        lab_to_xyz(self, &UNIT_WHITE)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELabColor {
+        CIELabColor::from_linear_with::<DefaultTransfer>(linear, white)
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, _white: &CIEXYZColor) -> CIELabColor {
+        xyz_to_lab_with::<T>(linear, &UNIT_WHITE)
+    }
+
+    fn linear_to_xyz_matrix(white: &CIEXYZColor) -> Matrix3 {
+        let &CIEXYZColor(xw, yw, zw) = white;
+        [[xw, 0.0, 0.0], [0.0, yw, 0.0], [0.0, 0.0, zw]]
+    }
+
+    fn xyz_to_linear_matrix(white: &CIEXYZColor) -> Matrix3 {
+        let &CIEXYZColor(xw, yw, zw) = white;
+        [[1.0 / xw, 0.0, 0.0], [0.0, 1.0 / yw, 0.0], [0.0, 0.0, 1.0 / zw]]
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn lab_gamma(val: f32) -> f32 {
-    if val <= 0.008856451679035631 {
-        return val / 0.12841854934601665 + 0.13793103448275862;
+    DefaultTransfer::lab_gamma(val)
+}
+
+#[inline(always)]
+pub(crate) fn lab_gamma_approx(val: f32) -> f32 {
+    if val <= 0.008856452 {
+        val / 0.12841855 + 0.13793103
     } else {
         let float_bits: u32 = f32::to_bits(val);
         let exponent: i32 = ((float_bits >> 23) & 0xff) as i32 - 126;
         let new_float_bits: u32 = (float_bits & 0x807fffff) | 0x3f000000;
         let x: f32 = f32::from_bits(new_float_bits);
 
-        let y = ((((x * 0.13380815400574772 - 0.6322612589104776) * 
-                    x + 1.2763453267854687) *
-                        x - 1.4815697155541507) * 
-                            x + 1.327585761559465) * 
-                                x + 0.37609281991938703;
+        let y = ((((x * 0.13380815 - 0.6322613) * 
+                    x + 1.2763454) *
+                        x - 1.4815698) * 
+                            x + 1.3275858) * 
+                                x + 0.37609282;
         
         let exponent_new = exponent / 3;
-        let correction_shift = f32::from_bits(((exponent_new as i32 + 127) as u32 & 0xff) << 23);
+        let correction_shift = f32::from_bits(((exponent_new + 127) as u32 & 0xff) << 23);
         let correction_index = ((exponent - exponent_new * 3) + 2) as usize;
         let correction_factor = CORRECTION_FACTOR_3RD_ROOT[correction_index];
-        return y * correction_shift * correction_factor;
+        y * correction_shift * correction_factor
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn lab_digamma(val: f32) -> f32 {
-    if val <= 0.20689655172413793 {
-        (val - 0.13793103448275862) * 0.12841854934601665
+    if val <= 0.20689656 {
+        (val - 0.13793103) * 0.12841855
     } else {
         val * val * val
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn xyz_to_lab(xyz: &CIEXYZColor, white: &CIEXYZColor) -> CIELabColor {
+    xyz_to_lab_with::<DefaultTransfer>(xyz, white)
+}
+
+#[inline(always)]
+pub(crate) fn xyz_to_lab_with<T: TransferFunctions>(xyz: &CIEXYZColor, white: &CIEXYZColor) -> CIELabColor {
     let &CIEXYZColor(x, y, z) = xyz;
     let &CIEXYZColor(xw, yw, zw) = white;
 
-    let x_gamma = lab_gamma(x / xw);
-    let y_gamma = lab_gamma(y / yw);
-    let z_gamma = lab_gamma(z / zw);
+    let x_gamma = T::lab_gamma(x / xw);
+    let y_gamma = T::lab_gamma(y / yw);
+    let z_gamma = T::lab_gamma(z / zw);
 
     CIELabColor(116.0 * y_gamma - 16.0, 500.0 * (x_gamma - y_gamma), 200.0 * (y_gamma - z_gamma))
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn lab_to_xyz(lab: &CIELabColor, white: &CIEXYZColor) -> CIEXYZColor {
     let &CIELabColor(l, a, b) = lab;
     let &CIEXYZColor(xw, yw, zw) = white;
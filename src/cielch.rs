@@ -0,0 +1,153 @@
+use crate::{TristimulusColor, CIEXYZColor, CIELabColor, xyz_to_lab, lab_to_xyz};
+use crate::color_space::ColorSpace;
+use crate::transfer::TransferFunctions;
+use crate::chromatic_adaptation::Matrix3;
+#[cfg(all(not(feature = "std"), not(test)))]
+use num_traits::Float;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct CIELChColor(pub f32, pub f32, pub f32);
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl CIELChColor {
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(l: f32, c: f32, h: f32) -> CIELChColor {
+        CIELChColor(l, c, h)
+    }
+}
+
+impl TristimulusColor for CIELChColor {
+
+    fn get_0(&self) -> f32 {
+        self.0
+    }
+
+    fn get_1(&self) -> f32 {
+        self.1
+    }
+
+    fn get_2(&self) -> f32 {
+        self.2
+    }
+}
+
+impl ColorSpace for CIELChColor {
+
+    const WHITE_RELATIVE: bool = true;
+
+    fn from_components(l: f32, c: f32, h: f32) -> CIELChColor {
+        CIELChColor(l, c, h)
+    }
+
+    fn to_linear(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        lch_to_lab(self).to_linear(white)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELChColor {
+        lab_to_lch(&CIELabColor::from_linear(linear, white))
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELChColor {
+        lab_to_lch(&CIELabColor::from_linear_with::<T>(linear, white))
+    }
+
+    fn linear_to_xyz_matrix(white: &CIEXYZColor) -> Matrix3 {
+        CIELabColor::linear_to_xyz_matrix(white)
+    }
+
+    fn xyz_to_linear_matrix(white: &CIEXYZColor) -> Matrix3 {
+        CIELabColor::xyz_to_linear_matrix(white)
+    }
+}
+
+/*
+Converts a pair of cartesian chromaticity coordinates into chroma and hue.
+The hue is given in degrees and always lies in the range [0; 360). Achromatic
+colors get a hue of 0, regardless of the signs of their (zero) coordinates.
+*/
+#[inline(always)]
+pub(crate) fn cartesian_to_polar(a: f32, b: f32) -> (f32, f32) {
+    let c = (a * a + b * b).sqrt();
+    if c == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let h = b.atan2(a).to_degrees();
+    if h < 0.0 {
+        (c, h + 360.0)
+    } else {
+        (c, h)
+    }
+}
+
+#[inline(always)]
+pub(crate) fn polar_to_cartesian(c: f32, h: f32) -> (f32, f32) {
+    let (sin_h, cos_h) = h.to_radians().sin_cos();
+    (c * cos_h, c * sin_h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lab_to_lch(lab: &CIELabColor) -> CIELChColor {
+    let &CIELabColor(l, a, b) = lab;
+    let (c, h) = cartesian_to_polar(a, b);
+    CIELChColor(l, c, h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lch_to_lab(lch: &CIELChColor) -> CIELabColor {
+    let &CIELChColor(l, c, h) = lch;
+    let (a, b) = polar_to_cartesian(c, h);
+    CIELabColor(l, a, b)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_lch(xyz: &CIEXYZColor, white: &CIEXYZColor) -> CIELChColor {
+    lab_to_lch(&xyz_to_lab(xyz, white))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lch_to_xyz(lch: &CIELChColor, white: &CIEXYZColor) -> CIEXYZColor {
+    lab_to_xyz(&lch_to_lab(lch), white)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::CompareMargin;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+
+    #[test]
+    fn test_lab_to_lch() {
+        assert!(lab_to_lch(&CIELabColor(60.0, 60.0, 30.0)).equal_within(CIELChColor(60.0, 67.082, 26.565), MARGIN));
+        assert!(lab_to_lch(&CIELabColor(76.069, -56.418, -21.731)).equal_within(CIELChColor(76.069, 60.458, 201.066), MARGIN));
+        assert!(lab_to_lch(&CIELabColor(50.0, 0.0, 0.0)).equal_within(CIELChColor(50.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_lch_to_lab() {
+        assert!(lch_to_lab(&CIELChColor(60.0, 67.082, 26.565)).equal_within(CIELabColor(60.0, 60.0, 30.0), MARGIN));
+        assert!(lch_to_lab(&CIELChColor(76.069, 60.458, 201.066)).equal_within(CIELabColor(76.069, -56.418, -21.731), MARGIN));
+        assert!(lch_to_lab(&CIELChColor(50.0, 0.0, 123.0)).equal_within(CIELabColor(50.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_xyz_to_lch() {
+        assert!(xyz_to_lch(&CIEXYZColor(0.44272, 0.28123, 0.14037), &D65).equal_within(CIELChColor(60.0, 67.082, 26.565), MARGIN));
+        assert!(xyz_to_lch(&CIEXYZColor(0.55, 0.9, 0.001), &D65).equal_within(CIELChColor(95.997, 176.890, 111.938), MARGIN));
+    }
+
+    #[test]
+    fn test_lch_to_xyz() {
+        assert!(lch_to_xyz(&CIELChColor(60.0, 67.082, 26.565), &D65).equal_within(CIEXYZColor(0.44272, 0.28123, 0.14037), MARGIN));
+        assert!(lch_to_xyz(&CIELChColor(76.069, 60.458, 201.066), &D65).equal_within(CIEXYZColor(0.3, 0.5, 0.8), MARGIN));
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::TristimulusColor;
+use crate::{CIEXYZColor, CIELuvColor};
+use crate::cielch::{cartesian_to_polar, polar_to_cartesian};
+use crate::color_space::ColorSpace;
+use crate::transfer::{TransferFunctions, DefaultTransfer};
+use crate::cieluv::{calc_uv_prime, xyz_to_luv_with, xyz_to_luv_precomputed_white, luv_to_xyz_precomputed_white};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct CIELChuvColor(pub f32, pub f32, pub f32);
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl CIELChuvColor {
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(l: f32, c: f32, h: f32) -> CIELChuvColor {
+        CIELChuvColor(l, c, h)
+    }
+}
+
+impl TristimulusColor for CIELChuvColor {
+
+    fn get_0(&self) -> f32 {
+        self.0
+    }
+
+    fn get_1(&self) -> f32 {
+        self.1
+    }
+
+    fn get_2(&self) -> f32 {
+        self.2
+    }
+}
+
+impl ColorSpace for CIELChuvColor {
+
+    const WHITE_RELATIVE: bool = true;
+
+    fn from_components(l: f32, c: f32, h: f32) -> CIELChuvColor {
+        CIELChuvColor(l, c, h)
+    }
+
+    fn to_linear(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        lchuv_to_xyz(self, white)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELChuvColor {
+        xyz_to_lchuv(linear, white)
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELChuvColor {
+        luv_to_lchuv(&xyz_to_luv_with::<T>(linear, white))
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn luv_to_lchuv(luv: &CIELuvColor) -> CIELChuvColor {
+    let &CIELuvColor(l, u, v) = luv;
+    let (c, h) = cartesian_to_polar(u, v);
+    CIELChuvColor(l, c, h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lchuv_to_luv(lchuv: &CIELChuvColor) -> CIELuvColor {
+    let &CIELChuvColor(l, c, h) = lchuv;
+    let (u, v) = polar_to_cartesian(c, h);
+    CIELuvColor(l, u, v)
+}
+
+#[inline(always)]
+pub(crate) fn xyz_to_lchuv_precomputed_white(xyz: &CIEXYZColor, yw: f32, u_prime_w: f32, v_prime_w: f32) -> CIELChuvColor {
+    luv_to_lchuv(&xyz_to_luv_precomputed_white::<DefaultTransfer>(xyz, yw, u_prime_w, v_prime_w))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_lchuv(xyz: &CIEXYZColor, white: &CIEXYZColor) -> CIELChuvColor {
+    let &CIEXYZColor(_, y_white, _) = white;
+    let (u_prime_white, v_prime_white) = calc_uv_prime(white);
+    xyz_to_lchuv_precomputed_white(xyz, y_white, u_prime_white, v_prime_white)
+}
+
+#[inline(always)]
+pub(crate) fn lchuv_to_xyz_precomputed_white(lchuv: &CIELChuvColor, yw: f32, u_prime_w: f32, v_prime_w: f32) -> CIEXYZColor {
+    luv_to_xyz_precomputed_white(&lchuv_to_luv(lchuv), yw, u_prime_w, v_prime_w)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lchuv_to_xyz(lchuv: &CIELChuvColor, white: &CIEXYZColor) -> CIEXYZColor {
+    let &CIEXYZColor(_, y_white, _) = white;
+    let (u_prime_white, v_prime_white) = calc_uv_prime(white);
+    lchuv_to_xyz_precomputed_white(lchuv, y_white, u_prime_white, v_prime_white)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::CompareMargin;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+
+    #[test]
+    fn test_luv_to_lchuv() {
+        assert!(luv_to_lchuv(&CIELuvColor(60.680, -53.444, 42.096)).equal_within(CIELChuvColor(60.680, 68.032, 141.774), MARGIN));
+        assert!(luv_to_lchuv(&CIELuvColor(50.0, 20.0, -20.0)).equal_within(CIELChuvColor(50.0, 28.284, 315.0), MARGIN));
+        assert!(luv_to_lchuv(&CIELuvColor(0.0, 0.0, 0.0)).equal_within(CIELChuvColor(0.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_lchuv_to_luv() {
+        assert!(lchuv_to_luv(&CIELChuvColor(60.680, 68.032, 141.774)).equal_within(CIELuvColor(60.680, -53.444, 42.096), MARGIN));
+        assert!(lchuv_to_luv(&CIELChuvColor(50.0, 28.284, 315.0)).equal_within(CIELuvColor(50.0, 20.0, -20.0), MARGIN));
+        assert!(lchuv_to_luv(&CIELChuvColor(0.0, 0.0, 42.0)).equal_within(CIELuvColor(0.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_xyz_to_lchuv() {
+        assert!(xyz_to_lchuv(&CIEXYZColor(0.16206, 0.28885, 0.16274), &D65).equal_within(CIELChuvColor(60.680, 68.032, 141.774), MARGIN));
+        assert!(xyz_to_lchuv(&CIEXYZColor(0.0, 0.0, 0.0), &D65).equal_within(CIELChuvColor(0.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_lchuv_to_xyz() {
+        assert!(lchuv_to_xyz(&CIELChuvColor(60.680, 68.032, 141.774), &D65).equal_within(CIEXYZColor(0.16206, 0.28885, 0.16274), MARGIN));
+        assert!(lchuv_to_xyz(&CIELChuvColor(0.0, 0.0, 0.0), &D65).equal_within(CIEXYZColor(0.0, 0.0, 0.0), MARGIN));
+    }
+}
@@ -1,24 +1,27 @@
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 use crate::TristimulusColor;
 use crate::CIEXYZColor;
+use crate::color_space::ColorSpace;
+use crate::transfer::{TransferFunctions, DefaultTransfer};
 
 const CORRECTION_FACTOR_3RD_ROOT: [f32; 5] = [
-    0.6299605249474366,
-    0.7937005259840998,
+    0.62996054,
+    0.7937005,
     1.0,
-    1.2599210498948732,
-    1.5874010519681994
+    1.2599211,
+    1.587401
 ];
 
-#[wasm_bindgen]
-#[derive(Debug)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
 pub struct CIELuvColor(pub f32, pub f32, pub f32);
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl CIELuvColor {
 
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(l: f32, u: f32, v: f32) -> CIELuvColor {
         CIELuvColor(l, u, v)
     }
@@ -39,23 +42,45 @@ impl TristimulusColor for CIELuvColor {
     }
 }
 
-fn luv_gamma(val: f32) -> f32 {
+impl ColorSpace for CIELuvColor {
+
+    const WHITE_RELATIVE: bool = true;
+
+    fn from_components(l: f32, u: f32, v: f32) -> CIELuvColor {
+        CIELuvColor(l, u, v)
+    }
+
+    fn to_linear(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        luv_to_xyz(self, white)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELuvColor {
+        xyz_to_luv(linear, white)
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, white: &CIEXYZColor) -> CIELuvColor {
+        xyz_to_luv_with::<T>(linear, white)
+    }
+}
+
+#[inline(always)]
+pub(crate) fn luv_gamma_approx(val: f32) -> f32 {
     let float_bits: u32 = f32::to_bits(val);
     let exponent: i32 = ((float_bits >> 23) & 0xff) as i32 - 126;
     let new_float_bits: u32 = (float_bits & 0x807fffff) | 0x3f000000;
     let x: f32 = f32::from_bits(new_float_bits);
 
-    let y = ((((x * 0.13380815400574772 - 0.6322612589104776) * 
-                x + 1.2763453267854687) *
-                    x - 1.4815697155541507) * 
-                        x + 1.327585761559465) * 
-                            x + 0.37609281991938703;
+    let y = ((((x * 0.13380815 - 0.6322613) * 
+                x + 1.2763454) *
+                    x - 1.4815698) * 
+                        x + 1.3275858) * 
+                            x + 0.37609282;
     
     let exponent_new = exponent / 3;
-    let correction_shift = f32::from_bits(((exponent_new as i32 + 127) as u32 & 0xff) << 23);
+    let correction_shift = f32::from_bits(((exponent_new + 127) as u32 & 0xff) << 23);
     let correction_index = ((exponent - exponent_new * 3) + 2) as usize;
     let correction_factor = CORRECTION_FACTOR_3RD_ROOT[correction_index];
-    return y * correction_shift * correction_factor;
+    y * correction_shift * correction_factor
 }
 
 #[inline(always)]
@@ -68,31 +93,36 @@ pub(crate) fn calc_uv_prime(xyz: &CIEXYZColor) -> (f32, f32) {
 
     let u_prime = 4.0 * x / uv_scale;
     let v_prime = 9.0 * y / uv_scale;
-    return (u_prime, v_prime);
+    (u_prime, v_prime)
 }
 
 #[inline(always)]
-pub(crate) fn xyz_to_luv_precomputed_white(xyz: &CIEXYZColor, yw: f32, u_prime_w: f32, v_prime_w: f32) -> CIELuvColor {
+pub(crate) fn xyz_to_luv_precomputed_white<T: TransferFunctions>(xyz: &CIEXYZColor, yw: f32, u_prime_w: f32, v_prime_w: f32) -> CIELuvColor {
     let &CIEXYZColor(_, y, _) = xyz;
     let y_scaled = y / yw;
-    let l = if y_scaled <= 0.008856451679035631 {
-        903.2962962962963 * y_scaled
+    let l = if y_scaled <= 0.008856452 {
+        903.2963 * y_scaled
     } else { 
-        116.0 * luv_gamma(y_scaled) - 16.0
+        116.0 * T::luv_gamma(y_scaled) - 16.0
     };
 
     let (u_prime, v_prime) = calc_uv_prime(xyz);
     let u = 13.0 * l * (u_prime - u_prime_w);
     let v = 13.0 * l * (v_prime - v_prime_w);
 
-    return CIELuvColor(l, u, v);
+    CIELuvColor(l, u, v)
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn xyz_to_luv(xyz: &CIEXYZColor, white: &CIEXYZColor) -> CIELuvColor {
+    xyz_to_luv_with::<DefaultTransfer>(xyz, white)
+}
+
+#[inline(always)]
+pub(crate) fn xyz_to_luv_with<T: TransferFunctions>(xyz: &CIEXYZColor, white: &CIEXYZColor) -> CIELuvColor {
     let &CIEXYZColor(_, y_white, _) = white;
     let (u_prime_white, v_prime_white) = calc_uv_prime(white);
-    return xyz_to_luv_precomputed_white(xyz, y_white, u_prime_white, v_prime_white);
+    xyz_to_luv_precomputed_white::<T>(xyz, y_white, u_prime_white, v_prime_white)
 }
 
 #[inline(always)]
@@ -105,7 +135,7 @@ pub(crate) fn luv_to_xyz_precomputed_white(luv: &CIELuvColor, yw: f32, u_prime_w
     let u_prime = u / (13.0 * l) + u_prime_w;
     let v_prime = v / (13.0 * l) + v_prime_w;
     let y = if l <= 8.0 {
-        yw * l * 0.0011070564598794539
+        yw * l * 0.0011070565
     } else {
         let temp = (l + 16.0) / 116.0;
         yw * temp * temp * temp
@@ -114,14 +144,14 @@ pub(crate) fn luv_to_xyz_precomputed_white(luv: &CIELuvColor, yw: f32, u_prime_w
     let x = y * (9.0 * u_prime) / (4.0 * v_prime);
     let z = y * (12.0 - 3.0 * u_prime - 20.0 * v_prime) / (4.0 * v_prime);
 
-    return CIEXYZColor(x, y, z);
+    CIEXYZColor(x, y, z)
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn luv_to_xyz(luv: &CIELuvColor, white: &CIEXYZColor) -> CIEXYZColor {
     let &CIEXYZColor(_, y_white, _) = white;
     let (u_prime_white, v_prime_white) = calc_uv_prime(white);
-    return luv_to_xyz_precomputed_white(luv, y_white, u_prime_white, v_prime_white);
+    luv_to_xyz_precomputed_white(luv, y_white, u_prime_white, v_prime_white)
 }
 
 #[cfg(test)]
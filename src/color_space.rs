@@ -0,0 +1,183 @@
+use crate::{TristimulusColor, CIEXYZColor, ChromaticAdaptation, ChromaticAdaptationMethod, SRGB_WHITE};
+use crate::chromatic_adaptation::{Matrix3, IDENTITY, multiply_matrix_vector, multiply_matrices};
+
+use crate::transfer::TransferFunctions;
+
+use core::marker::PhantomData;
+
+/*
+A color space that can be converted to and from CIEXYZ.
+
+Every conversion to CIEXYZ is split into a non-linear part, which maps the color onto
+"linear" tristimulus values, followed by a matrix. Converting between two spaces then
+only needs one fused matrix between both non-linear parts. Spaces without a linear
+intermediate simply return the CIEXYZ values from to_linear() and keep the identity matrices.
+
+Spaces for which WHITE_RELATIVE is false are defined relative to SRGB_WHITE and ignore
+the white point they are given.
+*/
+pub trait ColorSpace: TristimulusColor + Copy {
+
+    const WHITE_RELATIVE: bool;
+
+    fn from_components(c0: f32, c1: f32, c2: f32) -> Self;
+
+    fn to_linear(&self, white: &CIEXYZColor) -> CIEXYZColor;
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> Self;
+
+    /*
+    Same as to_linear() and from_linear(), but with the given transfer functions instead of
+    DefaultTransfer. Spaces whose conversions are exact anyway keep these defaults.
+    */
+    fn to_linear_with<T: TransferFunctions>(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        self.to_linear(white)
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, white: &CIEXYZColor) -> Self {
+        Self::from_linear(linear, white)
+    }
+
+    fn linear_to_xyz_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        IDENTITY
+    }
+
+    fn xyz_to_linear_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        IDENTITY
+    }
+
+    fn to_xyz(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        multiply_matrix_vector(&Self::linear_to_xyz_matrix(white), &self.to_linear(white))
+    }
+
+    fn from_xyz(xyz: &CIEXYZColor, white: &CIEXYZColor) -> Self {
+        Self::from_linear(&multiply_matrix_vector(&Self::xyz_to_linear_matrix(white), xyz), white)
+    }
+}
+
+/*
+Precomputed conversion between two color spaces. The matrices of both spaces and the
+chromatic adaptation between their white points are fused into a single matrix.
+*/
+#[derive(Debug, Copy, Clone)]
+pub struct Converter<From: ColorSpace, To: ColorSpace> {
+    pub(crate) source_white: CIEXYZColor,
+    pub(crate) target_white: CIEXYZColor,
+    pub(crate) matrix: Matrix3,
+    pub(crate) spaces: PhantomData<(From, To)>
+}
+
+impl<From: ColorSpace, To: ColorSpace> Converter<From, To> {
+
+    pub fn new(source_white: &CIEXYZColor, target_white: &CIEXYZColor, method: ChromaticAdaptationMethod) -> Converter<From, To> {
+        let source_white = if From::WHITE_RELATIVE { *source_white } else { SRGB_WHITE };
+        let target_white = if To::WHITE_RELATIVE { *target_white } else { SRGB_WHITE };
+
+        let CIEXYZColor(xs, ys, zs) = source_white;
+        let CIEXYZColor(xt, yt, zt) = target_white;
+        let adaptation = if xs == xt && ys == yt && zs == zt {
+            IDENTITY
+        } else {
+            ChromaticAdaptation::new(&source_white, &target_white, method).matrix()
+        };
+
+        let matrix = multiply_matrices(
+            &To::xyz_to_linear_matrix(&target_white),
+            &multiply_matrices(&adaptation, &From::linear_to_xyz_matrix(&source_white))
+        );
+
+        Converter { source_white, target_white, matrix, spaces: PhantomData }
+    }
+
+    #[inline(always)]
+    pub fn convert(&self, color: &From) -> To {
+        let linear = multiply_matrix_vector(&self.matrix, &color.to_linear(&self.source_white));
+        To::from_linear(&linear, &self.target_white)
+    }
+
+    #[inline(always)]
+    pub fn convert_with<T: TransferFunctions>(&self, color: &From) -> To {
+        let linear = multiply_matrix_vector(&self.matrix, &color.to_linear_with::<T>(&self.source_white));
+        To::from_linear_with::<T>(&linear, &self.target_white)
+    }
+}
+
+/*
+Converts a color between two arbitrary color spaces, adapting between the white points
+with the Bradford transform. Use Converter to convert many colors or to pick the transform.
+*/
+pub fn convert<From: ColorSpace, To: ColorSpace>(color: &From, source_white: &CIEXYZColor, target_white: &CIEXYZColor) -> To {
+    Converter::<From, To>::new(source_white, target_white, ChromaticAdaptationMethod::default()).convert(color)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::*;
+    use crate::CompareMargin;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+    const D50: CIEXYZColor = CIEXYZColor(0.96422, 1.0, 0.82521);
+
+    fn assert_round_trip<T: ColorSpace>(white: &CIEXYZColor) {
+        for &(r, g, b) in &[(0.0, 0.0, 0.0), (1.0, 1.0, 1.0), (0.8, 0.2, 0.1), (0.1, 0.6, 0.3), (0.25, 0.3, 0.9)] {
+            let srgb = SRGBColor(r, g, b);
+            let converted: T = convert(&srgb, &D65, white);
+            let back: SRGBColor = convert(&converted, white, &D65);
+            assert!(back.equal_within(srgb, MARGIN), "{:?} {:?}", back, srgb);
+        }
+    }
+
+    #[test]
+    fn test_convert_matches_free_functions() {
+        let srgb = SRGBColor(0.8, 0.2, 0.1);
+        let xyz = srgb_to_xyz(&srgb);
+
+        assert!(convert::<SRGBColor, CIEXYZColor>(&srgb, &D65, &D65).equal_within(xyz, MARGIN));
+        assert!(convert::<SRGBColor, LinearRGBColor>(&srgb, &D65, &D65).equal_within(srgb_to_linear_rgb(&srgb), MARGIN));
+        assert!(convert::<SRGBColor, CIELabColor>(&srgb, &SRGB_WHITE, &SRGB_WHITE).equal_within(xyz_to_lab(&xyz, &SRGB_WHITE), MARGIN));
+        assert!(convert::<SRGBColor, CIELChColor>(&srgb, &SRGB_WHITE, &SRGB_WHITE).equal_within(xyz_to_lch(&xyz, &SRGB_WHITE), MARGIN));
+        assert!(convert::<SRGBColor, CIELuvColor>(&srgb, &SRGB_WHITE, &SRGB_WHITE).equal_within(xyz_to_luv(&xyz, &SRGB_WHITE), MARGIN));
+        assert!(convert::<SRGBColor, CIELChuvColor>(&srgb, &SRGB_WHITE, &SRGB_WHITE).equal_within(xyz_to_lchuv(&xyz, &SRGB_WHITE), MARGIN));
+        assert!(convert::<SRGBColor, OklabColor>(&srgb, &D65, &D65).equal_within(linear_rgb_to_oklab(&srgb_to_linear_rgb(&srgb)), MARGIN));
+        assert!(convert::<SRGBColor, OklchColor>(&srgb, &D65, &D65).equal_within(oklab_to_oklch(&linear_rgb_to_oklab(&srgb_to_linear_rgb(&srgb))), MARGIN));
+    }
+
+    #[test]
+    fn test_convert_adapts_white() {
+        let xyz = CIEXYZColor(0.4124, 0.2126, 0.0193);
+        let expected = xyz_to_lab(&adapt_xyz(&xyz, &SRGB_WHITE, &D50, ChromaticAdaptationMethod::Bradford), &D50);
+        assert!(convert::<CIEXYZColor, CIELabColor>(&xyz, &D65, &D50).equal_within(expected, MARGIN));
+
+        // White-relative spaces map their white onto the white of the target.
+        let white: CIELuvColor = convert(&CIELabColor(100.0, 0.0, 0.0), &D50, &D65);
+        assert!(white.equal_within(CIELuvColor(100.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_convert_round_trips() {
+        assert_round_trip::<SRGBColor>(&D65);
+        assert_round_trip::<LinearRGBColor>(&D65);
+        assert_round_trip::<CIEXYZColor>(&D65);
+        assert_round_trip::<CIELabColor>(&D50);
+        assert_round_trip::<CIELChColor>(&D50);
+        assert_round_trip::<CIELuvColor>(&D50);
+        assert_round_trip::<CIELChuvColor>(&D50);
+        assert_round_trip::<OklabColor>(&D65);
+        assert_round_trip::<OklchColor>(&D65);
+    }
+
+    #[test]
+    fn test_converter_fuses_matrices() {
+        // Linear RGB to XYZ to Oklab's LMS collapses into the single published matrix.
+        let converter = Converter::<LinearRGBColor, OklabColor>::new(&D65, &D65, ChromaticAdaptationMethod::Bradford);
+        for (row, expected_row) in converter.matrix.iter().zip(crate::oklab::LINEAR_RGB_TO_LMS.iter()) {
+            for (val, expected_val) in row.iter().zip(expected_row.iter()) {
+                assert!((val - expected_val).abs() < 1.0e-5);
+            }
+        }
+    }
+}
@@ -0,0 +1,882 @@
+/*
+Double precision variants of the color types and conversions, chromatic adaptation, the CIE76
+and CIEDE2000 color differences and gamut mapping.
+
+Unlike their f32 counterparts, these do not use any polynomial approximations. The transfer
+functions are computed with powf() and cbrt(), and the matrices keep all of their digits, so
+the results are suitable as a reference or for colorimetric work such as checking measurement data.
+*/
+
+use crate::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use num_traits::Float;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+type Matrix3F64 = [[f64; 3]; 3];
+
+/*
+Declares a double precision color type, together with lossless conversions from
+the f32 type and rounding conversions back into it.
+*/
+macro_rules! color_f64 {
+    ($name:ident, $name_f32:ident, $c0:ident, $c1:ident, $c2:ident) => {
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct $name(pub f64, pub f64, pub f64);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $name {
+
+            #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+            pub fn new($c0: f64, $c1: f64, $c2: f64) -> $name {
+                $name($c0, $c1, $c2)
+            }
+        }
+
+        impl From<$name_f32> for $name {
+            fn from(color: $name_f32) -> $name {
+                $name(color.0 as f64, color.1 as f64, color.2 as f64)
+            }
+        }
+
+        impl From<$name> for $name_f32 {
+            fn from(color: $name) -> $name_f32 {
+                $name_f32(color.0 as f32, color.1 as f32, color.2 as f32)
+            }
+        }
+    };
+}
+
+color_f64!(SRGBColorF64, SRGBColor, r, g, b);
+color_f64!(LinearRGBColorF64, LinearRGBColor, r_lin, g_lin, b_lin);
+color_f64!(CIEXYZColorF64, CIEXYZColor, x, y, z);
+color_f64!(CIELabColorF64, CIELabColor, l, a, b);
+color_f64!(CIELChColorF64, CIELChColor, l, c, h);
+color_f64!(CIELuvColorF64, CIELuvColor, l, u, v);
+color_f64!(CIELChuvColorF64, CIELChuvColor, l, c, h);
+color_f64!(OklabColorF64, OklabColor, l, a, b);
+color_f64!(OklchColorF64, OklchColor, l, c, h);
+
+const LINEAR_RGB_TO_XYZ_F64: Matrix3F64 = [
+    [0.4123907992659595, 0.35758433938387796, 0.1804807884018343],
+    [0.21263900587151036, 0.7151686787677559, 0.07219231536073371],
+    [0.01933081871559185, 0.11919477979462599, 0.9505321522496606]
+];
+
+const XYZ_TO_LINEAR_RGB_F64: Matrix3F64 = [
+    [3.2409699419045213, -1.5373831775700935, -0.4986107602930033],
+    [-0.9692436362808798, 1.8759675015077206, 0.04155505740717561],
+    [0.05563007969699361, -0.20397695888897657, 1.0569715142428786]
+];
+
+const LINEAR_RGB_TO_LMS_F64: Matrix3F64 = [
+    [0.4122214708, 0.5363325363, 0.0514459929],
+    [0.2119034982, 0.6806995451, 0.1073969566],
+    [0.0883024619, 0.2817188376, 0.6299787005]
+];
+
+const LMS_TO_LINEAR_RGB_F64: Matrix3F64 = [
+    [4.0767416621, -3.3077115913, 0.2309699292],
+    [-1.2684380046, 2.6097574011, -0.3413193965],
+    [-0.0041960863, -0.7034186147, 1.7076147010]
+];
+
+// Products of the linear sRGB matrices, see XYZ_TO_LMS.
+const XYZ_TO_LMS_F64: Matrix3F64 = multiply_matrices_f64(&LINEAR_RGB_TO_LMS_F64, &XYZ_TO_LINEAR_RGB_F64);
+
+const LMS_TO_XYZ_F64: Matrix3F64 = multiply_matrices_f64(&LINEAR_RGB_TO_XYZ_F64, &LMS_TO_LINEAR_RGB_F64);
+
+// CIE constants as exact fractions instead of the rounded 0.008856 and 903.3.
+const CIE_EPSILON: f64 = 216.0 / 24389.0;
+const CIE_KAPPA: f64 = 24389.0 / 27.0;
+
+#[inline(always)]
+fn multiply_matrix_vector_f64(m: &Matrix3F64, v: (f64, f64, f64)) -> (f64, f64, f64) {
+    let (x, y, z) = v;
+    (
+        m[0][0] * x + m[0][1] * y + m[0][2] * z,
+        m[1][0] * x + m[1][1] * y + m[1][2] * z,
+        m[2][0] * x + m[2][1] * y + m[2][2] * z
+    )
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn srgb_gamma_f64(val: f64) -> f64 {
+    if val <= 0.0031308 {
+        val * 12.92
+    } else {
+        1.055 * val.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn srgb_digamma_f64(val: f64) -> f64 {
+    if val <= 0.04045 {
+        val / 12.92
+    } else {
+        ((val + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lab_gamma_f64(val: f64) -> f64 {
+    if val <= CIE_EPSILON {
+        (CIE_KAPPA * val + 16.0) / 116.0
+    } else {
+        val.cbrt()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lab_digamma_f64(val: f64) -> f64 {
+    if val <= 6.0 / 29.0 {
+        (116.0 * val - 16.0) / CIE_KAPPA
+    } else {
+        val * val * val
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn srgb_to_linear_rgb_f64(srgb: &SRGBColorF64) -> LinearRGBColorF64 {
+    let &SRGBColorF64(r, g, b) = srgb;
+    LinearRGBColorF64(srgb_digamma_f64(r), srgb_digamma_f64(g), srgb_digamma_f64(b))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn linear_rgb_to_srgb_f64(lin_rgb: &LinearRGBColorF64) -> SRGBColorF64 {
+    let &LinearRGBColorF64(r_lin, g_lin, b_lin) = lin_rgb;
+    SRGBColorF64(srgb_gamma_f64(r_lin), srgb_gamma_f64(g_lin), srgb_gamma_f64(b_lin))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn linear_rgb_to_xyz_f64(lin_rgb: &LinearRGBColorF64) -> CIEXYZColorF64 {
+    let &LinearRGBColorF64(r_lin, g_lin, b_lin) = lin_rgb;
+    let (x, y, z) = multiply_matrix_vector_f64(&LINEAR_RGB_TO_XYZ_F64, (r_lin, g_lin, b_lin));
+    CIEXYZColorF64(x, y, z)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_linear_rgb_f64(xyz: &CIEXYZColorF64) -> LinearRGBColorF64 {
+    let &CIEXYZColorF64(x, y, z) = xyz;
+    let (r_lin, g_lin, b_lin) = multiply_matrix_vector_f64(&XYZ_TO_LINEAR_RGB_F64, (x, y, z));
+    LinearRGBColorF64(r_lin, g_lin, b_lin)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn srgb_to_xyz_f64(srgb: &SRGBColorF64) -> CIEXYZColorF64 {
+    linear_rgb_to_xyz_f64(&srgb_to_linear_rgb_f64(srgb))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_srgb_f64(xyz: &CIEXYZColorF64) -> SRGBColorF64 {
+    linear_rgb_to_srgb_f64(&xyz_to_linear_rgb_f64(xyz))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_lab_f64(xyz: &CIEXYZColorF64, white: &CIEXYZColorF64) -> CIELabColorF64 {
+    let &CIEXYZColorF64(x, y, z) = xyz;
+    let &CIEXYZColorF64(xw, yw, zw) = white;
+
+    let x_gamma = lab_gamma_f64(x / xw);
+    let y_gamma = lab_gamma_f64(y / yw);
+    let z_gamma = lab_gamma_f64(z / zw);
+
+    CIELabColorF64(116.0 * y_gamma - 16.0, 500.0 * (x_gamma - y_gamma), 200.0 * (y_gamma - z_gamma))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lab_to_xyz_f64(lab: &CIELabColorF64, white: &CIEXYZColorF64) -> CIEXYZColorF64 {
+    let &CIELabColorF64(l, a, b) = lab;
+    let &CIEXYZColorF64(xw, yw, zw) = white;
+
+    let y_gamma = (l + 16.0) / 116.0;
+    let x_gamma = y_gamma + a / 500.0;
+    let z_gamma = y_gamma - b / 200.0;
+
+    CIEXYZColorF64(xw * lab_digamma_f64(x_gamma), yw * lab_digamma_f64(y_gamma), zw * lab_digamma_f64(z_gamma))
+}
+
+fn cartesian_to_polar_f64(a: f64, b: f64) -> (f64, f64) {
+    let c = (a * a + b * b).sqrt();
+    if c == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let h = b.atan2(a).to_degrees();
+    if h < 0.0 {
+        (c, h + 360.0)
+    } else {
+        (c, h)
+    }
+}
+
+fn polar_to_cartesian_f64(c: f64, h: f64) -> (f64, f64) {
+    let (sin_h, cos_h) = h.to_radians().sin_cos();
+    (c * cos_h, c * sin_h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lab_to_lch_f64(lab: &CIELabColorF64) -> CIELChColorF64 {
+    let &CIELabColorF64(l, a, b) = lab;
+    let (c, h) = cartesian_to_polar_f64(a, b);
+    CIELChColorF64(l, c, h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lch_to_lab_f64(lch: &CIELChColorF64) -> CIELabColorF64 {
+    let &CIELChColorF64(l, c, h) = lch;
+    let (a, b) = polar_to_cartesian_f64(c, h);
+    CIELabColorF64(l, a, b)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_lch_f64(xyz: &CIEXYZColorF64, white: &CIEXYZColorF64) -> CIELChColorF64 {
+    lab_to_lch_f64(&xyz_to_lab_f64(xyz, white))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lch_to_xyz_f64(lch: &CIELChColorF64, white: &CIEXYZColorF64) -> CIEXYZColorF64 {
+    lab_to_xyz_f64(&lch_to_lab_f64(lch), white)
+}
+
+fn calc_uv_prime_f64(xyz: &CIEXYZColorF64) -> (f64, f64) {
+    let &CIEXYZColorF64(x, y, z) = xyz;
+    let uv_scale = x + 15.0 * y + 3.0 * z;
+    if uv_scale == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    (4.0 * x / uv_scale, 9.0 * y / uv_scale)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_luv_f64(xyz: &CIEXYZColorF64, white: &CIEXYZColorF64) -> CIELuvColorF64 {
+    let &CIEXYZColorF64(_, y, _) = xyz;
+    let &CIEXYZColorF64(_, yw, _) = white;
+
+    let y_scaled = y / yw;
+    let l = if y_scaled <= CIE_EPSILON {
+        CIE_KAPPA * y_scaled
+    } else {
+        116.0 * y_scaled.cbrt() - 16.0
+    };
+
+    let (u_prime, v_prime) = calc_uv_prime_f64(xyz);
+    let (u_prime_w, v_prime_w) = calc_uv_prime_f64(white);
+    CIELuvColorF64(l, 13.0 * l * (u_prime - u_prime_w), 13.0 * l * (v_prime - v_prime_w))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn luv_to_xyz_f64(luv: &CIELuvColorF64, white: &CIEXYZColorF64) -> CIEXYZColorF64 {
+    let &CIELuvColorF64(l, u, v) = luv;
+    let &CIEXYZColorF64(_, yw, _) = white;
+    if l == 0.0 {
+        return CIEXYZColorF64(0.0, 0.0, 0.0);
+    }
+
+    let (u_prime_w, v_prime_w) = calc_uv_prime_f64(white);
+    let u_prime = u / (13.0 * l) + u_prime_w;
+    let v_prime = v / (13.0 * l) + v_prime_w;
+    let y = if l <= 8.0 {
+        yw * l / CIE_KAPPA
+    } else {
+        let temp = (l + 16.0) / 116.0;
+        yw * temp * temp * temp
+    };
+
+    let x = y * (9.0 * u_prime) / (4.0 * v_prime);
+    let z = y * (12.0 - 3.0 * u_prime - 20.0 * v_prime) / (4.0 * v_prime);
+    CIEXYZColorF64(x, y, z)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn luv_to_lchuv_f64(luv: &CIELuvColorF64) -> CIELChuvColorF64 {
+    let &CIELuvColorF64(l, u, v) = luv;
+    let (c, h) = cartesian_to_polar_f64(u, v);
+    CIELChuvColorF64(l, c, h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lchuv_to_luv_f64(lchuv: &CIELChuvColorF64) -> CIELuvColorF64 {
+    let &CIELChuvColorF64(l, c, h) = lchuv;
+    let (u, v) = polar_to_cartesian_f64(c, h);
+    CIELuvColorF64(l, u, v)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_lchuv_f64(xyz: &CIEXYZColorF64, white: &CIEXYZColorF64) -> CIELChuvColorF64 {
+    luv_to_lchuv_f64(&xyz_to_luv_f64(xyz, white))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lchuv_to_xyz_f64(lchuv: &CIELChuvColorF64, white: &CIEXYZColorF64) -> CIEXYZColorF64 {
+    luv_to_xyz_f64(&lchuv_to_luv_f64(lchuv), white)
+}
+
+fn lms_to_oklab_f64(lms: (f64, f64, f64)) -> OklabColorF64 {
+    let (l, m, s) = lms;
+    let l_gamma = l.cbrt();
+    let m_gamma = m.cbrt();
+    let s_gamma = s.cbrt();
+
+    OklabColorF64(
+        l_gamma * 0.2104542553 + m_gamma * 0.7936177850 + s_gamma * -0.0040720468,
+        l_gamma * 1.9779984951 + m_gamma * -2.4285922050 + s_gamma * 0.4505937099,
+        l_gamma * 0.0259040371 + m_gamma * 0.7827717662 + s_gamma * -0.8086757660
+    )
+}
+
+fn oklab_to_lms_f64(oklab: &OklabColorF64) -> (f64, f64, f64) {
+    let &OklabColorF64(l, a, b) = oklab;
+
+    let l_gamma = l + a * 0.3963377774 + b * 0.2158037573;
+    let m_gamma = l + a * -0.1055613458 + b * -0.0638541728;
+    let s_gamma = l + a * -0.0894841775 + b * -1.2914855480;
+
+    (l_gamma * l_gamma * l_gamma, m_gamma * m_gamma * m_gamma, s_gamma * s_gamma * s_gamma)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn linear_rgb_to_oklab_f64(lin_rgb: &LinearRGBColorF64) -> OklabColorF64 {
+    let &LinearRGBColorF64(r_lin, g_lin, b_lin) = lin_rgb;
+    lms_to_oklab_f64(multiply_matrix_vector_f64(&LINEAR_RGB_TO_LMS_F64, (r_lin, g_lin, b_lin)))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_to_linear_rgb_f64(oklab: &OklabColorF64) -> LinearRGBColorF64 {
+    let (r_lin, g_lin, b_lin) = multiply_matrix_vector_f64(&LMS_TO_LINEAR_RGB_F64, oklab_to_lms_f64(oklab));
+    LinearRGBColorF64(r_lin, g_lin, b_lin)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_oklab_f64(xyz: &CIEXYZColorF64) -> OklabColorF64 {
+    let &CIEXYZColorF64(x, y, z) = xyz;
+    lms_to_oklab_f64(multiply_matrix_vector_f64(&XYZ_TO_LMS_F64, (x, y, z)))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_to_xyz_f64(oklab: &OklabColorF64) -> CIEXYZColorF64 {
+    let (x, y, z) = multiply_matrix_vector_f64(&LMS_TO_XYZ_F64, oklab_to_lms_f64(oklab));
+    CIEXYZColorF64(x, y, z)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_to_oklch_f64(oklab: &OklabColorF64) -> OklchColorF64 {
+    let &OklabColorF64(l, a, b) = oklab;
+    let (c, h) = cartesian_to_polar_f64(a, b);
+    OklchColorF64(l, c, h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklch_to_oklab_f64(oklch: &OklchColorF64) -> OklabColorF64 {
+    let &OklchColorF64(l, c, h) = oklch;
+    let (a, b) = polar_to_cartesian_f64(c, h);
+    OklabColorF64(l, a, b)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_oklch_f64(xyz: &CIEXYZColorF64) -> OklchColorF64 {
+    oklab_to_oklch_f64(&xyz_to_oklab_f64(xyz))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklch_to_xyz_f64(oklch: &OklchColorF64) -> CIEXYZColorF64 {
+    oklab_to_xyz_f64(&oklch_to_oklab_f64(oklch))
+}
+
+const VON_KRIES_F64: Matrix3F64 = [
+    [0.40024, 0.70760, -0.08081],
+    [-0.22630, 1.16532, 0.04570],
+    [0.0, 0.0, 0.91822]
+];
+
+const BRADFORD_F64: Matrix3F64 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296]
+];
+
+const CAT02_F64: Matrix3F64 = [
+    [0.7328, 0.4296, -0.1624],
+    [-0.7036, 1.6975, 0.0061],
+    [0.0030, 0.0136, 0.9834]
+];
+
+const CAT16_F64: Matrix3F64 = [
+    [0.401288, 0.650173, -0.051461],
+    [-0.250268, 1.204414, 0.045854],
+    [-0.002079, 0.048952, 0.953127]
+];
+
+fn cone_response_matrix_f64(method: ChromaticAdaptationMethod) -> Matrix3F64 {
+    match method {
+        ChromaticAdaptationMethod::VonKries => VON_KRIES_F64,
+        ChromaticAdaptationMethod::Bradford => BRADFORD_F64,
+        ChromaticAdaptationMethod::CAT02 => CAT02_F64,
+        ChromaticAdaptationMethod::CAT16 => CAT16_F64
+    }
+}
+
+const fn multiply_matrices_f64(a: &Matrix3F64, b: &Matrix3F64) -> Matrix3F64 {
+    let mut out = [[0.0; 3]; 3];
+    let mut i = 0;
+    while i < 3 {
+        let mut j = 0;
+        while j < 3 {
+            out[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+            j += 1;
+        }
+        i += 1;
+    }
+    out
+}
+
+fn invert_matrix_f64(m: &Matrix3F64) -> Matrix3F64 {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+
+    let c00 = cofactor(1, 2, 1, 2);
+    let c01 = -cofactor(1, 2, 0, 2);
+    let c02 = cofactor(1, 2, 0, 1);
+    let det = m[0][0] * c00 + m[0][1] * c01 + m[0][2] * c02;
+
+    [
+        [c00 / det, -cofactor(0, 2, 1, 2) / det, cofactor(0, 1, 1, 2) / det],
+        [c01 / det, cofactor(0, 2, 0, 2) / det, -cofactor(0, 1, 0, 2) / det],
+        [c02 / det, -cofactor(0, 2, 0, 1) / det, cofactor(0, 1, 0, 1) / det]
+    ]
+}
+
+/*
+Double precision counterpart of ChromaticAdaptation. The cone response matrices are
+the published ones, so the transform is exact up to f64 rounding.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct ChromaticAdaptationF64 {
+    matrix: Matrix3F64
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ChromaticAdaptationF64 {
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(source_white: &CIEXYZColorF64, target_white: &CIEXYZColorF64, method: ChromaticAdaptationMethod) -> ChromaticAdaptationF64 {
+        let cone_response = cone_response_matrix_f64(method);
+        let &CIEXYZColorF64(xs, ys, zs) = source_white;
+        let &CIEXYZColorF64(xt, yt, zt) = target_white;
+        let (rho_s, gamma_s, beta_s) = multiply_matrix_vector_f64(&cone_response, (xs, ys, zs));
+        let (rho_t, gamma_t, beta_t) = multiply_matrix_vector_f64(&cone_response, (xt, yt, zt));
+
+        let scale = [
+            [rho_t / rho_s, 0.0, 0.0],
+            [0.0, gamma_t / gamma_s, 0.0],
+            [0.0, 0.0, beta_t / beta_s]
+        ];
+
+        let matrix = multiply_matrices_f64(&invert_matrix_f64(&cone_response), &multiply_matrices_f64(&scale, &cone_response));
+        ChromaticAdaptationF64 { matrix }
+    }
+
+    pub fn apply(&self, xyz: &CIEXYZColorF64) -> CIEXYZColorF64 {
+        let &CIEXYZColorF64(x, y, z) = xyz;
+        let (x, y, z) = multiply_matrix_vector_f64(&self.matrix, (x, y, z));
+        CIEXYZColorF64(x, y, z)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn adapt_xyz_f64(xyz: &CIEXYZColorF64, source_white: &CIEXYZColorF64, target_white: &CIEXYZColorF64, method: ChromaticAdaptationMethod) -> CIEXYZColorF64 {
+    ChromaticAdaptationF64::new(source_white, target_white, method).apply(xyz)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delta_e_76_f64(lab1: &CIELabColorF64, lab2: &CIELabColorF64) -> f64 {
+    let &CIELabColorF64(l1, a1, b1) = lab1;
+    let &CIELabColorF64(l2, a2, b2) = lab2;
+
+    let delta_l = l1 - l2;
+    let delta_a = a1 - a2;
+    let delta_b = b1 - b2;
+    (delta_l * delta_l + delta_a * delta_a + delta_b * delta_b).sqrt()
+}
+
+/*
+CIEDE2000 color difference, see delta_e_2000() for the f32 version and its reference.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delta_e_2000_f64(lab1: &CIELabColorF64, lab2: &CIELabColorF64) -> f64 {
+    let &CIELabColorF64(l1, a1, b1) = lab1;
+    let &CIELabColorF64(l2, a2, b2) = lab2;
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let c_mean = (c1 + c2) * 0.5;
+    let c_mean_7 = c_mean.powi(7);
+    let g = 0.5 * (1.0 - (c_mean_7 / (c_mean_7 + 6103515625.0)).sqrt());
+
+    let (c1_prime, h1_prime) = cartesian_to_polar_f64((1.0 + g) * a1, b1);
+    let (c2_prime, h2_prime) = cartesian_to_polar_f64((1.0 + g) * a2, b2);
+
+    let delta_l_prime = l2 - l1;
+    let delta_c_prime = c2_prime - c1_prime;
+
+    let chroma_product = c1_prime * c2_prime;
+    let h_difference = h2_prime - h1_prime;
+    let delta_h_prime = if chroma_product == 0.0 {
+        0.0
+    } else if h_difference.abs() <= 180.0 {
+        h_difference
+    } else if h_difference > 180.0 {
+        h_difference - 360.0
+    } else {
+        h_difference + 360.0
+    };
+    let delta_big_h_prime = 2.0 * chroma_product.sqrt() * (delta_h_prime * 0.5).to_radians().sin();
+
+    let l_prime_mean = (l1 + l2) * 0.5;
+    let c_prime_mean = (c1_prime + c2_prime) * 0.5;
+    let h_sum = h1_prime + h2_prime;
+    let h_prime_mean = if chroma_product == 0.0 {
+        h_sum
+    } else if h_difference.abs() <= 180.0 {
+        h_sum * 0.5
+    } else if h_sum < 360.0 {
+        (h_sum + 360.0) * 0.5
+    } else {
+        (h_sum - 360.0) * 0.5
+    };
+
+    let t = 1.0
+        - 0.17 * (h_prime_mean - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_prime_mean).to_radians().cos()
+        + 0.32 * (3.0 * h_prime_mean + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_prime_mean - 63.0).to_radians().cos();
+
+    let delta_theta = 30.0 * (-((h_prime_mean - 275.0) / 25.0).powi(2)).exp();
+    let c_prime_mean_7 = c_prime_mean.powi(7);
+    let r_c = 2.0 * (c_prime_mean_7 / (c_prime_mean_7 + 6103515625.0)).sqrt();
+    let l_offset_squared = (l_prime_mean - 50.0) * (l_prime_mean - 50.0);
+    let s_l = 1.0 + 0.015 * l_offset_squared / (20.0 + l_offset_squared).sqrt();
+    let s_c = 1.0 + 0.045 * c_prime_mean;
+    let s_h = 1.0 + 0.015 * c_prime_mean * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let l_term = delta_l_prime / s_l;
+    let c_term = delta_c_prime / s_c;
+    let h_term = delta_big_h_prime / s_h;
+    (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+}
+
+/*
+Gamut mapping in double precision. The searches are the same as in gamut_mapping.rs, but
+without a tolerance for approximation errors and with finer steps.
+*/
+const JND_OKLAB_F64: f64 = 0.02;
+
+const CHROMA_EPSILON_OKLCH_F64: f64 = 1.0e-6;
+
+const CHROMA_ITERATIONS_LAB_F64: usize = 30;
+
+const LIGHTNESS_SAMPLES_LAB_F64: usize = 20;
+
+const LIGHTNESS_ITERATIONS_LAB_F64: usize = 40;
+
+// 1 / golden ratio
+const GOLDEN_SECTION_F64: f64 = 0.6180339887498949;
+
+fn linear_rgb_in_gamut_f64(lin_rgb: &LinearRGBColorF64) -> bool {
+    let &LinearRGBColorF64(r, g, b) = lin_rgb;
+    (0.0..=1.0).contains(&r) && (0.0..=1.0).contains(&g) && (0.0..=1.0).contains(&b)
+}
+
+fn clip_linear_rgb_f64(lin_rgb: &LinearRGBColorF64) -> LinearRGBColorF64 {
+    let &LinearRGBColorF64(r, g, b) = lin_rgb;
+    LinearRGBColorF64(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+}
+
+fn delta_e_ok_f64(oklab1: &OklabColorF64, oklab2: &OklabColorF64) -> f64 {
+    let delta_l = oklab1.0 - oklab2.0;
+    let delta_a = oklab1.1 - oklab2.1;
+    let delta_b = oklab1.2 - oklab2.2;
+    (delta_l * delta_l + delta_a * delta_a + delta_b * delta_b).sqrt()
+}
+
+fn reduce_chroma_oklch_f64(l: f64, c: f64, h: f64) -> f64 {
+    let mut min = 0.0;
+    let mut max = c;
+    let mut min_in_gamut = true;
+    while max - min > CHROMA_EPSILON_OKLCH_F64 {
+        let chroma = (min + max) * 0.5;
+        let current_oklab = oklch_to_oklab_f64(&OklchColorF64(l, chroma, h));
+        let current = oklab_to_linear_rgb_f64(&current_oklab);
+
+        if min_in_gamut && linear_rgb_in_gamut_f64(&current) {
+            min = chroma;
+            continue;
+        }
+
+        let delta_e = delta_e_ok_f64(&linear_rgb_to_oklab_f64(&clip_linear_rgb_f64(&current)), &current_oklab);
+        if delta_e < JND_OKLAB_F64 {
+            if JND_OKLAB_F64 - delta_e < CHROMA_EPSILON_OKLCH_F64 {
+                return chroma;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    min
+}
+
+fn map_oklch_chroma_reduction_f64(lin_rgb: &LinearRGBColorF64) -> LinearRGBColorF64 {
+    let OklchColorF64(l, c, h) = oklab_to_oklch_f64(&linear_rgb_to_oklab_f64(lin_rgb));
+    if l >= 1.0 {
+        return LinearRGBColorF64(1.0, 1.0, 1.0);
+    } else if l <= 0.0 {
+        return LinearRGBColorF64(0.0, 0.0, 0.0);
+    }
+
+    let clipped = clip_linear_rgb_f64(lin_rgb);
+    if delta_e_ok_f64(&linear_rgb_to_oklab_f64(&clipped), &linear_rgb_to_oklab_f64(lin_rgb)) < JND_OKLAB_F64 {
+        return clipped;
+    }
+
+    let chroma = reduce_chroma_oklch_f64(l, c, h);
+    clip_linear_rgb_f64(&oklab_to_linear_rgb_f64(&oklch_to_oklab_f64(&OklchColorF64(l, chroma, h))))
+}
+
+fn srgb_white_f64() -> CIEXYZColorF64 {
+    srgb_to_xyz_f64(&SRGBColorF64(1.0, 1.0, 1.0))
+}
+
+fn lch_to_linear_rgb_f64(lch: &CIELChColorF64) -> LinearRGBColorF64 {
+    xyz_to_linear_rgb_f64(&lch_to_xyz_f64(lch, &srgb_white_f64()))
+}
+
+fn max_chroma_lab_f64(l: f64, h: f64, c_upper: f64) -> f64 {
+    let mut min = 0.0;
+    let mut max = c_upper;
+    for _ in 0..CHROMA_ITERATIONS_LAB_F64 {
+        let chroma = (min + max) * 0.5;
+        if linear_rgb_in_gamut_f64(&lch_to_linear_rgb_f64(&CIELChColorF64(l, chroma, h))) {
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    min
+}
+
+fn map_min_de_f64(lin_rgb: &LinearRGBColorF64) -> LinearRGBColorF64 {
+    let CIELChColorF64(l, c, h) = xyz_to_lch_f64(&linear_rgb_to_xyz_f64(lin_rgb), &srgb_white_f64());
+    if l >= 100.0 {
+        return LinearRGBColorF64(1.0, 1.0, 1.0);
+    } else if l <= 0.0 {
+        return LinearRGBColorF64(0.0, 0.0, 0.0);
+    }
+
+    let distance = |l_candidate: f64| {
+        let c_candidate = max_chroma_lab_f64(l_candidate, h, c);
+        let delta_l = l - l_candidate;
+        let delta_c = c - c_candidate;
+        (delta_l * delta_l + delta_c * delta_c, c_candidate)
+    };
+
+    let l_min = (l - c).max(0.0);
+    let l_max = (l + c).min(100.0);
+    let step = (l_max - l_min) / LIGHTNESS_SAMPLES_LAB_F64 as f64;
+    let mut best_l = l;
+    let mut best_distance = c * c;
+    for i in 0..=LIGHTNESS_SAMPLES_LAB_F64 {
+        let l_candidate = l_min + i as f64 * step;
+        let (d, _) = distance(l_candidate);
+        if d < best_distance {
+            best_distance = d;
+            best_l = l_candidate;
+        }
+    }
+
+    let mut low = (best_l - step).max(l_min);
+    let mut high = (best_l + step).min(l_max);
+    let mut inner_low = high - (high - low) * GOLDEN_SECTION_F64;
+    let mut inner_high = low + (high - low) * GOLDEN_SECTION_F64;
+    let mut distance_low = distance(inner_low).0;
+    let mut distance_high = distance(inner_high).0;
+    for _ in 0..LIGHTNESS_ITERATIONS_LAB_F64 {
+        if distance_low < distance_high {
+            high = inner_high;
+            inner_high = inner_low;
+            distance_high = distance_low;
+            inner_low = high - (high - low) * GOLDEN_SECTION_F64;
+            distance_low = distance(inner_low).0;
+        } else {
+            low = inner_low;
+            inner_low = inner_high;
+            distance_low = distance_high;
+            inner_high = low + (high - low) * GOLDEN_SECTION_F64;
+            distance_high = distance(inner_high).0;
+        }
+    }
+
+    let l_mapped = (low + high) * 0.5;
+    let (_, c_mapped) = distance(l_mapped);
+    clip_linear_rgb_f64(&lch_to_linear_rgb_f64(&CIELChColorF64(l_mapped, c_mapped, h)))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_map_linear_rgb_f64(lin_rgb: &LinearRGBColorF64, method: GamutMappingMethod) -> LinearRGBColorF64 {
+    if method == GamutMappingMethod::Clip || linear_rgb_in_gamut_f64(lin_rgb) {
+        return clip_linear_rgb_f64(lin_rgb);
+    }
+
+    match method {
+        GamutMappingMethod::Clip => clip_linear_rgb_f64(lin_rgb),
+        GamutMappingMethod::OklchChromaReduction => map_oklch_chroma_reduction_f64(lin_rgb),
+        GamutMappingMethod::MinDE => map_min_de_f64(lin_rgb)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_map_xyz_to_srgb_f64(xyz: &CIEXYZColorF64, method: GamutMappingMethod) -> SRGBColorF64 {
+    linear_rgb_to_srgb_f64(&gamut_map_linear_rgb_f64(&xyz_to_linear_rgb_f64(xyz), method))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const MARGIN: f64 = 1.0e-9;
+
+    const D65: CIEXYZColorF64 = CIEXYZColorF64(0.95047, 1.0, 1.08883);
+
+    fn equal_within(a: (f64, f64, f64), b: (f64, f64, f64), margin: f64) -> bool {
+        (a.0 - b.0).abs() < margin && (a.1 - b.1).abs() < margin && (a.2 - b.2).abs() < margin
+    }
+
+    #[test]
+    fn test_srgb_transfer_functions_f64() {
+        for i in 0..=1000 {
+            let val = i as f64 / 1000.0;
+            assert!((srgb_gamma_f64(srgb_digamma_f64(val)) - val).abs() < 1.0e-12);
+        }
+        assert!((srgb_digamma_f64(0.5) - 0.21404114048223255).abs() < 1.0e-15);
+    }
+
+    #[test]
+    fn test_srgb_to_xyz_f64() {
+        let CIEXYZColorF64(x, y, z) = srgb_to_xyz_f64(&SRGBColorF64(1.0, 0.0, 0.0));
+        assert!(equal_within((x, y, z), (0.4123907992659595, 0.21263900587151036, 0.01933081871559185), MARGIN));
+
+        let CIEXYZColorF64(x, y, z) = srgb_to_xyz_f64(&SRGBColorF64(1.0, 1.0, 1.0));
+        assert!(equal_within((x, y, z), (0.9504559270516716, 1.0, 1.0890577507598784), MARGIN));
+    }
+
+    #[test]
+    fn test_xyz_to_lab_f64() {
+        let white = srgb_to_xyz_f64(&SRGBColorF64(1.0, 1.0, 1.0));
+        let CIELabColorF64(l, a, b) = xyz_to_lab_f64(&srgb_to_xyz_f64(&SRGBColorF64(1.0, 0.0, 0.0)), &white);
+        assert!(equal_within((l, a, b), (53.23711559542937, 80.0901135231038, 67.20326351172214), MARGIN));
+    }
+
+    #[test]
+    fn test_round_trips_f64() {
+        let srgb = SRGBColorF64(0.8, 0.2, 0.1);
+        let xyz = srgb_to_xyz_f64(&srgb);
+        let values = |c: CIEXYZColorF64| (c.0, c.1, c.2);
+
+        assert!(equal_within(values(lab_to_xyz_f64(&xyz_to_lab_f64(&xyz, &D65), &D65)), values(xyz), MARGIN));
+        assert!(equal_within(values(lch_to_xyz_f64(&xyz_to_lch_f64(&xyz, &D65), &D65)), values(xyz), MARGIN));
+        assert!(equal_within(values(luv_to_xyz_f64(&xyz_to_luv_f64(&xyz, &D65), &D65)), values(xyz), MARGIN));
+        assert!(equal_within(values(lchuv_to_xyz_f64(&xyz_to_lchuv_f64(&xyz, &D65), &D65)), values(xyz), MARGIN));
+
+        // The published Oklab matrices only have ten digits and are not exact inverses of each other.
+        assert!(equal_within(values(oklab_to_xyz_f64(&xyz_to_oklab_f64(&xyz))), values(xyz), 1.0e-6));
+
+        let SRGBColorF64(r, g, b) = linear_rgb_to_srgb_f64(&oklab_to_linear_rgb_f64(&oklch_to_oklab_f64(&oklab_to_oklch_f64(&linear_rgb_to_oklab_f64(&srgb_to_linear_rgb_f64(&srgb))))));
+        assert!(equal_within((r, g, b), (0.8, 0.2, 0.1), 1.0e-6));
+    }
+
+    #[test]
+    fn test_xyz_to_oklch_f64() {
+        let xyz = srgb_to_xyz_f64(&SRGBColorF64(1.0, 0.0, 0.0));
+        let OklchColorF64(l, c, h) = xyz_to_oklch_f64(&xyz);
+        assert!(equal_within((l, c, h), (0.627955, 0.257683, 29.2339), 1.0e-4));
+
+        let CIEXYZColorF64(x, y, z) = oklch_to_xyz_f64(&OklchColorF64(l, c, h));
+        assert!(equal_within((x, y, z), (xyz.0, xyz.1, xyz.2), 1.0e-6));
+    }
+
+    #[test]
+    fn test_chromatic_adaptation_f64() {
+        let d50 = CIEXYZColorF64(0.96422, 1.0, 0.82521);
+        let values = |c: CIEXYZColorF64| (c.0, c.1, c.2);
+        for method in [ChromaticAdaptationMethod::VonKries, ChromaticAdaptationMethod::Bradford, ChromaticAdaptationMethod::CAT02, ChromaticAdaptationMethod::CAT16] {
+            assert!(equal_within(values(adapt_xyz_f64(&D65, &D65, &d50, method)), values(d50), 1.0e-12));
+            assert!(equal_within(values(adapt_xyz_f64(&d50, &d50, &D65, method)), values(D65), 1.0e-12));
+        }
+
+        // Bradford D65 to D50 as published by Lindbloom.
+        let CIEXYZColorF64(x, y, z) = adapt_xyz_f64(&CIEXYZColorF64(1.0, 0.0, 0.0), &D65, &d50, ChromaticAdaptationMethod::Bradford);
+        assert!(equal_within((x, y, z), (1.0478112, 0.0295424, -0.0092345), 1.0e-7));
+    }
+
+    #[test]
+    fn test_delta_e_f64() {
+        // Test data from Sharma et al., Table 1.
+        let pairs = [
+            ((50.0000, 2.6772, -79.7751), (50.0000, 0.0000, -82.7485), 2.0425),
+            ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0011), 7.2195),
+            ((50.0000, 2.5000, 0.0000), (73.0000, 25.0000, -18.0000), 27.1492),
+            ((60.2574, -34.0099, 36.2677), (60.4626, -34.1751, 39.4387), 1.2644),
+            ((2.0776, 0.0795, -1.1350), (0.9033, -0.0636, -0.5514), 0.9082)
+        ];
+        for ((l1, a1, b1), (l2, a2, b2), expected) in pairs {
+            let (lab1, lab2) = (CIELabColorF64(l1, a1, b1), CIELabColorF64(l2, a2, b2));
+            assert!((delta_e_2000_f64(&lab1, &lab2) - expected).abs() < 1.0e-4);
+            assert!((delta_e_2000_f64(&lab2, &lab1) - expected).abs() < 1.0e-4);
+        }
+
+        assert_eq!(delta_e_76_f64(&CIELabColorF64(50.0, 3.0, 4.0), &CIELabColorF64(50.0, 0.0, 0.0)), 5.0);
+    }
+
+    #[test]
+    fn test_gamut_mapping_f64() {
+        let methods = [GamutMappingMethod::Clip, GamutMappingMethod::OklchChromaReduction, GamutMappingMethod::MinDE];
+        let in_unit_range = |srgb: &SRGBColorF64| (0.0..=1.0).contains(&srgb.0) && (0.0..=1.0).contains(&srgb.1) && (0.0..=1.0).contains(&srgb.2);
+
+        for method in methods {
+            let srgb = SRGBColorF64(0.2, 0.3, 0.4);
+            let SRGBColorF64(r, g, b) = gamut_map_xyz_to_srgb_f64(&srgb_to_xyz_f64(&srgb), method);
+            assert!(equal_within((r, g, b), (0.2, 0.3, 0.4), 1.0e-12));
+
+            let out_of_gamut = lab_to_xyz_f64(&CIELabColorF64(50.0, 100.0, -100.0), &srgb_white_f64());
+            assert!(in_unit_range(&gamut_map_xyz_to_srgb_f64(&out_of_gamut, method)));
+        }
+
+        // The reduced chroma is within one JND of its clipped color, and lightness and hue are kept.
+        let oklch = OklchColorF64(0.7, 0.4, 150.0);
+        let mapped = gamut_map_linear_rgb_f64(&oklab_to_linear_rgb_f64(&oklch_to_oklab_f64(&oklch)), GamutMappingMethod::OklchChromaReduction);
+        let mapped_oklab = linear_rgb_to_oklab_f64(&mapped);
+        let OklchColorF64(_, c, _) = oklab_to_oklch_f64(&mapped_oklab);
+        assert!(delta_e_ok_f64(&mapped_oklab, &oklch_to_oklab_f64(&OklchColorF64(0.7, c, 150.0))) < JND_OKLAB_F64);
+        assert!(c < 0.4);
+        for j in 0..360 {
+            let chroma = reduce_chroma_oklch_f64(0.5, 0.4, j as f64);
+            let reduced = oklch_to_oklab_f64(&OklchColorF64(0.5, chroma, j as f64));
+            let clipped = linear_rgb_to_oklab_f64(&clip_linear_rgb_f64(&oklab_to_linear_rgb_f64(&reduced)));
+            assert!(delta_e_ok_f64(&clipped, &reduced) < JND_OKLAB_F64);
+        }
+    }
+
+    #[test]
+    fn test_matches_f32() {
+        let srgb = SRGBColor(0.3, 0.6, 0.9);
+        let lab_f32 = xyz_to_lab(&srgb_to_xyz(&srgb), &CIEXYZColor::from(D65));
+        let CIELabColorF64(l, a, b) = xyz_to_lab_f64(&srgb_to_xyz_f64(&SRGBColorF64::from(srgb)), &D65);
+        assert!(equal_within((l, a, b), (lab_f32.0 as f64, lab_f32.1 as f64, lab_f32.2 as f64), 1.0e-2));
+    }
+}
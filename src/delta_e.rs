@@ -0,0 +1,254 @@
+use crate::CIELabColor;
+use crate::cielch::cartesian_to_polar;
+#[cfg(all(not(feature = "std"), not(test)))]
+use num_traits::Float;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CIE94Application {
+    GraphicArts = 0,
+    Textiles = 1
+}
+
+/*
+CIE 1976 color difference, which is the euclidean distance in CIELab.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delta_e_76(lab1: &CIELabColor, lab2: &CIELabColor) -> f32 {
+    let &CIELabColor(l1, a1, b1) = lab1;
+    let &CIELabColor(l2, a2, b2) = lab2;
+
+    let delta_l = l1 - l2;
+    let delta_a = a1 - a2;
+    let delta_b = b1 - b2;
+    (delta_l * delta_l + delta_a * delta_a + delta_b * delta_b).sqrt()
+}
+
+/*
+CIE 1994 color difference. This difference is not symmetric, lab1 is the reference color.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delta_e_94(lab1: &CIELabColor, lab2: &CIELabColor, application: CIE94Application) -> f32 {
+    let &CIELabColor(l1, a1, b1) = lab1;
+    let &CIELabColor(l2, a2, b2) = lab2;
+
+    let (k_l, k_1, k_2) = match application {
+        CIE94Application::GraphicArts => (1.0, 0.045, 0.015),
+        CIE94Application::Textiles => (2.0, 0.048, 0.014)
+    };
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+
+    let delta_l = l1 - l2;
+    let delta_c = c1 - c2;
+    let delta_a = a1 - a2;
+    let delta_b = b1 - b2;
+    let delta_h_squared = (delta_a * delta_a + delta_b * delta_b - delta_c * delta_c).max(0.0);
+
+    let s_c = 1.0 + k_1 * c1;
+    let s_h = 1.0 + k_2 * c1;
+
+    let l_term = delta_l / k_l;
+    let c_term = delta_c / s_c;
+    (l_term * l_term + c_term * c_term + delta_h_squared / (s_h * s_h)).sqrt()
+}
+
+/*
+CMC l:c color difference. Common weights are 2:1 for acceptability and 1:1 for perceptibility.
+This difference is not symmetric, lab1 is the reference color.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delta_e_cmc(lab1: &CIELabColor, lab2: &CIELabColor, lightness: f32, chroma: f32) -> f32 {
+    let &CIELabColor(l1, a1, b1) = lab1;
+    let &CIELabColor(l2, a2, b2) = lab2;
+
+    let (c1, h1) = cartesian_to_polar(a1, b1);
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+
+    let delta_l = l1 - l2;
+    let delta_c = c1 - c2;
+    let delta_a = a1 - a2;
+    let delta_b = b1 - b2;
+    let delta_h_squared = (delta_a * delta_a + delta_b * delta_b - delta_c * delta_c).max(0.0);
+
+    let s_l = if l1 < 16.0 {
+        0.511
+    } else {
+        0.040975 * l1 / (1.0 + 0.01765 * l1)
+    };
+    let s_c = 0.0638 * c1 / (1.0 + 0.0131 * c1) + 0.638;
+
+    let c1_4 = c1 * c1 * c1 * c1;
+    let f = (c1_4 / (c1_4 + 1900.0)).sqrt();
+    let t = if (164.0..=345.0).contains(&h1) {
+        0.56 + (0.2 * (h1 + 168.0).to_radians().cos()).abs()
+    } else {
+        0.36 + (0.4 * (h1 + 35.0).to_radians().cos()).abs()
+    };
+    let s_h = s_c * (f * t + 1.0 - f);
+
+    let l_term = delta_l / (lightness * s_l);
+    let c_term = delta_c / (chroma * s_c);
+    (l_term * l_term + c_term * c_term + delta_h_squared / (s_h * s_h)).sqrt()
+}
+
+/*
+CIEDE2000 color difference, with all parametric weighting factors set to 1.
+
+Implemented after G. Sharma, W. Wu, E. N. Dalal, "The CIEDE2000 Color-Difference Formula:
+Implementation Notes, Supplementary Test Data, and Mathematical Observations", 2005.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delta_e_2000(lab1: &CIELabColor, lab2: &CIELabColor) -> f32 {
+    let &CIELabColor(l1, a1, b1) = lab1;
+    let &CIELabColor(l2, a2, b2) = lab2;
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let c_mean = (c1 + c2) * 0.5;
+    let c_mean_7 = c_mean.powi(7);
+    let g = 0.5 * (1.0 - (c_mean_7 / (c_mean_7 + 6103515625.0)).sqrt());
+
+    let (c1_prime, h1_prime) = cartesian_to_polar((1.0 + g) * a1, b1);
+    let (c2_prime, h2_prime) = cartesian_to_polar((1.0 + g) * a2, b2);
+
+    let delta_l_prime = l2 - l1;
+    let delta_c_prime = c2_prime - c1_prime;
+
+    let chroma_product = c1_prime * c2_prime;
+    let h_difference = h2_prime - h1_prime;
+    let delta_h_prime = if chroma_product == 0.0 {
+        0.0
+    } else if h_difference.abs() <= 180.0 {
+        h_difference
+    } else if h_difference > 180.0 {
+        h_difference - 360.0
+    } else {
+        h_difference + 360.0
+    };
+    let delta_big_h_prime = 2.0 * chroma_product.sqrt() * (delta_h_prime * 0.5).to_radians().sin();
+
+    let l_prime_mean = (l1 + l2) * 0.5;
+    let c_prime_mean = (c1_prime + c2_prime) * 0.5;
+    let h_sum = h1_prime + h2_prime;
+    let h_prime_mean = if chroma_product == 0.0 {
+        h_sum
+    } else if h_difference.abs() <= 180.0 {
+        h_sum * 0.5
+    } else if h_sum < 360.0 {
+        (h_sum + 360.0) * 0.5
+    } else {
+        (h_sum - 360.0) * 0.5
+    };
+
+    let t = 1.0
+        - 0.17 * (h_prime_mean - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_prime_mean).to_radians().cos()
+        + 0.32 * (3.0 * h_prime_mean + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_prime_mean - 63.0).to_radians().cos();
+
+    let delta_theta = 30.0 * (-((h_prime_mean - 275.0) / 25.0).powi(2)).exp();
+    let c_prime_mean_7 = c_prime_mean.powi(7);
+    let r_c = 2.0 * (c_prime_mean_7 / (c_prime_mean_7 + 6103515625.0)).sqrt();
+    let l_offset_squared = (l_prime_mean - 50.0) * (l_prime_mean - 50.0);
+    let s_l = 1.0 + 0.015 * l_offset_squared / (20.0 + l_offset_squared).sqrt();
+    let s_c = 1.0 + 0.045 * c_prime_mean;
+    let s_h = 1.0 + 0.015 * c_prime_mean * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let l_term = delta_l_prime / s_l;
+    let c_term = delta_c_prime / s_c;
+    let h_term = delta_big_h_prime / s_h;
+    (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    // Test data from Sharma et al., Table 1.
+    const SHARMA_DATA: [(f32, f32, f32, f32, f32, f32, f32); 34] = [
+        (50.0000, 2.6772, -79.7751, 50.0000, 0.0000, -82.7485, 2.0425),
+        (50.0000, 3.1571, -77.2803, 50.0000, 0.0000, -82.7485, 2.8615),
+        (50.0000, 2.8361, -74.0200, 50.0000, 0.0000, -82.7485, 3.4412),
+        (50.0000, -1.3802, -84.2814, 50.0000, 0.0000, -82.7485, 1.0000),
+        (50.0000, -1.1848, -84.8006, 50.0000, 0.0000, -82.7485, 1.0000),
+        (50.0000, -0.9009, -85.5211, 50.0000, 0.0000, -82.7485, 1.0000),
+        (50.0000, 0.0000, 0.0000, 50.0000, -1.0000, 2.0000, 2.3669),
+        (50.0000, -1.0000, 2.0000, 50.0000, 0.0000, 0.0000, 2.3669),
+        (50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0009, 7.1792),
+        (50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0010, 7.1792),
+        (50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0011, 7.2195),
+        (50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0012, 7.2195),
+        (50.0000, -0.0010, 2.4900, 50.0000, 0.0009, -2.4900, 4.8045),
+        (50.0000, -0.0010, 2.4900, 50.0000, 0.0010, -2.4900, 4.8045),
+        (50.0000, -0.0010, 2.4900, 50.0000, 0.0011, -2.4900, 4.7461),
+        (50.0000, 2.5000, 0.0000, 50.0000, 0.0000, -2.5000, 4.3065),
+        (50.0000, 2.5000, 0.0000, 73.0000, 25.0000, -18.0000, 27.1492),
+        (50.0000, 2.5000, 0.0000, 61.0000, -5.0000, 29.0000, 22.8977),
+        (50.0000, 2.5000, 0.0000, 56.0000, -27.0000, -3.0000, 31.9030),
+        (50.0000, 2.5000, 0.0000, 58.0000, 24.0000, 15.0000, 19.4535),
+        (50.0000, 2.5000, 0.0000, 50.0000, 3.1736, 0.5854, 1.0000),
+        (50.0000, 2.5000, 0.0000, 50.0000, 3.2972, 0.0000, 1.0000),
+        (50.0000, 2.5000, 0.0000, 50.0000, 1.8634, 0.5757, 1.0000),
+        (50.0000, 2.5000, 0.0000, 50.0000, 3.2592, 0.3350, 1.0000),
+        (60.2574, -34.0099, 36.2677, 60.4626, -34.1751, 39.4387, 1.2644),
+        (63.0109, -31.0961, -5.8663, 62.8187, -29.7946, -4.0864, 1.2630),
+        (61.2901, 3.7196, -5.3901, 61.4292, 2.2480, -4.9620, 1.8731),
+        (35.0831, -44.1164, 3.7933, 35.0232, -40.0716, 1.5901, 1.8645),
+        (22.7233, 20.0904, -46.6940, 23.0331, 14.9730, -42.5619, 2.0373),
+        (36.4612, 47.8580, 18.3852, 36.2715, 50.5065, 21.2231, 1.4146),
+        (90.8027, -2.0831, 1.4410, 91.1528, -1.6435, 0.0447, 1.4441),
+        (90.9257, -0.5406, -0.9208, 88.6381, -0.8985, -0.7239, 1.5381),
+        (6.7747, -0.2908, -2.4247, 5.8714, -0.0985, -2.2286, 0.6377),
+        (2.0776, 0.0795, -1.1350, 0.9033, -0.0636, -0.5514, 0.9082)
+    ];
+
+    #[test]
+    fn test_delta_e_2000() {
+        for &(l1, a1, b1, l2, a2, b2, expected) in SHARMA_DATA.iter() {
+            let lab1 = CIELabColor(l1, a1, b1);
+            let lab2 = CIELabColor(l2, a2, b2);
+            assert!((delta_e_2000(&lab1, &lab2) - expected).abs() < MARGIN, "{:?} {:?}", lab1, lab2);
+            assert!((delta_e_2000(&lab2, &lab1) - expected).abs() < MARGIN, "{:?} {:?}", lab2, lab1);
+        }
+    }
+
+    #[test]
+    fn test_delta_e_76() {
+        assert!((delta_e_76(&CIELabColor(50.0, 0.0, 0.0), &CIELabColor(50.0, 3.0, 4.0)) - 5.0).abs() < MARGIN);
+        assert!((delta_e_76(&CIELabColor(60.0, 60.0, 30.0), &CIELabColor(40.0, 60.0, 30.0)) - 20.0).abs() < MARGIN);
+        assert!((delta_e_76(&CIELabColor(50.0, 2.6772, -79.7751), &CIELabColor(50.0, 0.0, -82.7485)) - 4.0010).abs() < MARGIN);
+    }
+
+    #[test]
+    fn test_delta_e_94() {
+        let lab1 = CIELabColor(50.0, 2.6772, -79.7751);
+        let lab2 = CIELabColor(50.0, 0.0, -82.7485);
+        assert!((delta_e_94(&lab1, &lab2, CIE94Application::GraphicArts) - 1.3950).abs() < MARGIN);
+        assert!((delta_e_94(&lab1, &lab2, CIE94Application::Textiles) - 1.4230).abs() < MARGIN);
+
+        let lab1 = CIELabColor(60.2574, -34.0099, 36.2677);
+        let lab2 = CIELabColor(60.4626, -34.1751, 39.4387);
+        assert!((delta_e_94(&lab1, &lab2, CIE94Application::GraphicArts) - 1.3910).abs() < MARGIN);
+    }
+
+    #[test]
+    fn test_delta_e_cmc() {
+        let lab1 = CIELabColor(50.0, 2.6772, -79.7751);
+        let lab2 = CIELabColor(50.0, 0.0, -82.7485);
+        assert!((delta_e_cmc(&lab1, &lab2, 1.0, 1.0) - 1.7387).abs() < MARGIN);
+
+        let lab1 = CIELabColor(60.2574, -34.0099, 36.2677);
+        let lab2 = CIELabColor(60.4626, -34.1751, 39.4387);
+        assert!((delta_e_cmc(&lab1, &lab2, 1.0, 1.0) - 1.4282).abs() < MARGIN);
+        assert!((delta_e_cmc(&lab1, &lab2, 2.0, 1.0) - 1.4205).abs() < MARGIN);
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use core::fmt;
+
+/*
+Errors of the functions exported to JavaScript.
+
+In JavaScript they are thrown as Error objects with the name "ColorSpacesError" and the
+description of the error as message, see js/main.js.
+*/
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ColorSpacesError {
+    // A CIExyY white point which is not a possible white, for example with y = 0.
+    InvalidWhitePoint { x: f32, y: f32, yy: f32 },
+    // A region whose offset or length is not a multiple of 4, i.e. not made of whole RGBA pixels.
+    NotWholePixels { offset: usize, len: usize },
+    // A region that does not lie within its buffer.
+    OutOfBounds { offset: usize, len: usize, buffer_len: usize },
+    // An input region that overlaps the output region it is written to.
+    OverlappingRegions { offset: usize, len: usize, output_offset: usize, output_len: usize },
+    // A row stride in bytes which is shorter than a row of the image or not a whole number of pixels.
+    InvalidStride { width: usize, stride: usize },
+    // A rectangle that does not lie within its image.
+    RectOutOfBounds { x: usize, y: usize, width: usize, height: usize, image_width: usize, image_height: usize },
+    // A color space name without a ColorSpaceId.
+    UnsupportedSpace,
+    // A maximum color difference which is not a positive, finite number.
+    InvalidMaxDeltaE { max_delta_e: f32 },
+    // Regions that have to be of the same length, but are not.
+    LengthMismatch { len: usize, other_len: usize }
+}
+
+impl fmt::Display for ColorSpacesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ColorSpacesError::InvalidWhitePoint { x, y, yy } =>
+                write!(f, "invalid white point x = {}, y = {}, Y = {}", x, y, yy),
+            ColorSpacesError::NotWholePixels { offset, len } =>
+                write!(f, "region at {} with length {} does not consist of whole RGBA pixels", offset, len),
+            ColorSpacesError::OutOfBounds { offset, len, buffer_len } =>
+                write!(f, "region at {} with length {} is outside of the buffer of length {}", offset, len, buffer_len),
+            ColorSpacesError::OverlappingRegions { offset, len, output_offset, output_len } =>
+                write!(f, "region at {} with length {} overlaps the output region at {} with length {}", offset, len, output_offset, output_len),
+            ColorSpacesError::InvalidStride { width, stride } =>
+                write!(f, "row stride of {} bytes does not fit rows of {} RGBA pixels", stride, width),
+            ColorSpacesError::RectOutOfBounds { x, y, width, height, image_width, image_height } =>
+                write!(f, "rectangle at ({}, {}) of size {}x{} is outside of the image of size {}x{}", x, y, width, height, image_width, image_height),
+            ColorSpacesError::UnsupportedSpace =>
+                write!(f, "unsupported color space"),
+            ColorSpacesError::InvalidMaxDeltaE { max_delta_e } =>
+                write!(f, "maximum color difference {} is not a positive, finite number", max_delta_e),
+            ColorSpacesError::LengthMismatch { len, other_len } =>
+                write!(f, "region with length {} does not match the region with length {}", len, other_len)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ColorSpacesError {}
+
+#[cfg(feature = "wasm")]
+impl From<ColorSpacesError> for JsValue {
+    fn from(error: ColorSpacesError) -> JsValue {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("ColorSpacesError");
+        js_error.into()
+    }
+}
+
+/*
+White points are given as CIExyY. x and y have to lie in the triangle of possible chromaticities,
+with y > 0 since it is divided by, and the luminance Y has to be positive.
+*/
+pub(crate) fn check_white_point(x: f32, y: f32, yy: f32) -> Result<(), ColorSpacesError> {
+    if x.is_finite() && yy.is_finite() && x >= 0.0 && y > 0.0 && x + y <= 1.0 && yy > 0.0 {
+        Ok(())
+    } else {
+        Err(ColorSpacesError::InvalidWhitePoint { x, y, yy })
+    }
+}
+
+// Regions of pixels have to start and end on whole pixels.
+#[cfg(feature = "std")]
+pub(crate) fn check_pixels(offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    if !offset.is_multiple_of(4) || !len.is_multiple_of(4) {
+        return Err(ColorSpacesError::NotWholePixels { offset, len });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_check_white_point() {
+        assert!(check_white_point(0.31271, 0.32902, 1.0).is_ok());
+        assert!(check_white_point(0.34567, 0.35850, 100.0).is_ok());
+
+        assert_eq!(check_white_point(0.3, 0.0, 1.0), Err(ColorSpacesError::InvalidWhitePoint { x: 0.3, y: 0.0, yy: 1.0 }));
+        assert!(check_white_point(0.3, -0.3, 1.0).is_err());
+        assert!(check_white_point(-0.1, 0.3, 1.0).is_err());
+        assert!(check_white_point(0.7, 0.4, 1.0).is_err());
+        assert!(check_white_point(0.3, 0.3, 0.0).is_err());
+        assert!(check_white_point(f32::NAN, 0.3, 1.0).is_err());
+        assert!(check_white_point(0.3, f32::NAN, 1.0).is_err());
+        assert!(check_white_point(0.3, 0.3, f32::INFINITY).is_err());
+    }
+}
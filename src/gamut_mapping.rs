@@ -0,0 +1,383 @@
+use crate::{SRGBColor, LinearRGBColor, CIEXYZColor, CIELabColor, CIELChColor, OklabColor, OklchColor, SRGB_WHITE};
+use crate::{linear_rgb_to_srgb, xyz_to_linear_rgb, linear_rgb_to_xyz, xyz_to_lab, lab_to_xyz, lab_to_lch, lch_to_lab};
+use crate::{linear_rgb_to_oklab, oklab_to_linear_rgb, oklab_to_oklch, oklch_to_oklab};
+use crate::{CIELuvColor, luv_to_xyz, ChromaticAdaptation, ChromaticAdaptationMethod};
+#[cfg(all(not(feature = "std"), not(test)))]
+use num_traits::Float;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/*
+Linear RGB values within this distance outside of [0; 1] still count as in gamut.
+This absorbs the error of the fast transfer function approximations.
+*/
+const GAMUT_TOLERANCE: f32 = 1.0e-4;
+
+// Just noticeable difference in Oklab, as used by CSS Color 4.
+const JND_OKLAB: f32 = 0.02;
+
+const CHROMA_EPSILON_OKLCH: f32 = 1.0e-4;
+
+const CHROMA_ITERATIONS_LAB: usize = 14;
+
+const LIGHTNESS_SAMPLES_LAB: usize = 10;
+
+const LIGHTNESS_ITERATIONS_LAB: usize = 18;
+
+// 1 / golden ratio
+const GOLDEN_SECTION: f32 = 0.618034;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum GamutMappingMethod {
+    // Clamp each channel of the linear RGB color.
+    #[default]
+    Clip = 0,
+    // CSS Color 4 gamut mapping: reduce Oklch chroma until clipping becomes unnoticeable.
+    OklchChromaReduction = 1,
+    /*
+    Hue preserving minimum CIELab distance to the gamut boundary. By far the slowest method,
+    every out-of-gamut color takes about 30 searches for the largest in-gamut chroma.
+    */
+    MinDE = 2
+}
+
+#[inline(always)]
+pub(crate) fn linear_rgb_in_gamut(lin_rgb: &LinearRGBColor, tolerance: f32) -> bool {
+    let &LinearRGBColor(r, g, b) = lin_rgb;
+    r >= -tolerance && r <= 1.0 + tolerance &&
+    g >= -tolerance && g <= 1.0 + tolerance &&
+    b >= -tolerance && b <= 1.0 + tolerance
+}
+
+#[inline(always)]
+fn clip_linear_rgb(lin_rgb: &LinearRGBColor) -> LinearRGBColor {
+    let &LinearRGBColor(r, g, b) = lin_rgb;
+    LinearRGBColor(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+}
+
+fn delta_e_ok(oklab1: &OklabColor, oklab2: &OklabColor) -> f32 {
+    let delta_l = oklab1.0 - oklab2.0;
+    let delta_a = oklab1.1 - oklab2.1;
+    let delta_b = oklab1.2 - oklab2.2;
+    (delta_l * delta_l + delta_a * delta_a + delta_b * delta_b).sqrt()
+}
+
+/*
+CSS Color Module Level 4, section 13.2: binary search on Oklch chroma for the most
+chromatic color at the given lightness and hue whose clipped version is within one JND of it.
+*/
+fn reduce_chroma_oklch(l: f32, c: f32, h: f32) -> f32 {
+    let mut min = 0.0;
+    let mut max = c;
+    let mut min_in_gamut = true;
+    while max - min > CHROMA_EPSILON_OKLCH {
+        let chroma = (min + max) * 0.5;
+        let current_oklab = oklch_to_oklab(&OklchColor(l, chroma, h));
+        let current = oklab_to_linear_rgb(&current_oklab);
+
+        if min_in_gamut && linear_rgb_in_gamut(&current, GAMUT_TOLERANCE) {
+            min = chroma;
+            continue;
+        }
+
+        let delta_e = delta_e_ok(&linear_rgb_to_oklab(&clip_linear_rgb(&current)), &current_oklab);
+        if delta_e < JND_OKLAB {
+            if JND_OKLAB - delta_e < CHROMA_EPSILON_OKLCH {
+                return chroma;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    min
+}
+
+/*
+The result is the clipped color of the reduced chroma. Clipping the last chroma tried
+instead would return a color more than one JND away if that chroma was rejected.
+*/
+fn map_oklch_chroma_reduction(lin_rgb: &LinearRGBColor) -> LinearRGBColor {
+    let OklchColor(l, c, h) = oklab_to_oklch(&linear_rgb_to_oklab(lin_rgb));
+    if l >= 1.0 {
+        return LinearRGBColor(1.0, 1.0, 1.0);
+    } else if l <= 0.0 {
+        return LinearRGBColor(0.0, 0.0, 0.0);
+    }
+
+    let clipped = clip_linear_rgb(lin_rgb);
+    if delta_e_ok(&linear_rgb_to_oklab(&clipped), &linear_rgb_to_oklab(lin_rgb)) < JND_OKLAB {
+        return clipped;
+    }
+
+    let chroma = reduce_chroma_oklch(l, c, h);
+    clip_linear_rgb(&oklab_to_linear_rgb(&oklch_to_oklab(&OklchColor(l, chroma, h))))
+}
+
+#[inline(always)]
+fn lch_to_linear_rgb(lch: &CIELChColor) -> LinearRGBColor {
+    xyz_to_linear_rgb(&lab_to_xyz(&lch_to_lab(lch), &SRGB_WHITE))
+}
+
+/*
+Largest chroma in [0; c_upper] at the given CIELab lightness and hue which is still inside of sRGB.
+*/
+fn max_chroma_lab(l: f32, h: f32, c_upper: f32) -> f32 {
+    let mut min = 0.0;
+    let mut max = c_upper;
+    for _ in 0..CHROMA_ITERATIONS_LAB {
+        let chroma = (min + max) * 0.5;
+        if linear_rgb_in_gamut(&lch_to_linear_rgb(&CIELChColor(l, chroma, h)), GAMUT_TOLERANCE) {
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    min
+}
+
+/*
+Hue preserving MINDE: within the CIELab plane of constant hue, finds the in-gamut
+color with the smallest euclidean distance to the input. The gray at the same lightness
+is c away, so only lightnesses within c of the input can be closer. These are sampled
+coarsely, followed by a golden section search around the best sample.
+*/
+fn map_min_de(lin_rgb: &LinearRGBColor) -> LinearRGBColor {
+    let CIELChColor(l, c, h) = lab_to_lch(&xyz_to_lab(&linear_rgb_to_xyz(lin_rgb), &SRGB_WHITE));
+    if l >= 100.0 {
+        return LinearRGBColor(1.0, 1.0, 1.0);
+    } else if l <= 0.0 {
+        return LinearRGBColor(0.0, 0.0, 0.0);
+    }
+
+    let distance = |l_candidate: f32| {
+        let c_candidate = max_chroma_lab(l_candidate, h, c);
+        let delta_l = l - l_candidate;
+        let delta_c = c - c_candidate;
+        (delta_l * delta_l + delta_c * delta_c, c_candidate)
+    };
+
+    let l_min = (l - c).max(0.0);
+    let l_max = (l + c).min(100.0);
+    let step = (l_max - l_min) / LIGHTNESS_SAMPLES_LAB as f32;
+    let mut best_l = l;
+    let mut best_distance = c * c;
+    for i in 0..=LIGHTNESS_SAMPLES_LAB {
+        let l_candidate = l_min + i as f32 * step;
+        let (d, _) = distance(l_candidate);
+        if d < best_distance {
+            best_distance = d;
+            best_l = l_candidate;
+        }
+    }
+
+    // Each step keeps one of the two inner points, so only one new distance is needed.
+    let mut low = (best_l - step).max(l_min);
+    let mut high = (best_l + step).min(l_max);
+    let mut inner_low = high - (high - low) * GOLDEN_SECTION;
+    let mut inner_high = low + (high - low) * GOLDEN_SECTION;
+    let mut distance_low = distance(inner_low).0;
+    let mut distance_high = distance(inner_high).0;
+    for _ in 0..LIGHTNESS_ITERATIONS_LAB {
+        if distance_low < distance_high {
+            high = inner_high;
+            inner_high = inner_low;
+            distance_high = distance_low;
+            inner_low = high - (high - low) * GOLDEN_SECTION;
+            distance_low = distance(inner_low).0;
+        } else {
+            low = inner_low;
+            inner_low = inner_high;
+            distance_low = distance_high;
+            inner_high = low + (high - low) * GOLDEN_SECTION;
+            distance_high = distance(inner_high).0;
+        }
+    }
+
+    let l_mapped = (low + high) * 0.5;
+    let (_, c_mapped) = distance(l_mapped);
+    clip_linear_rgb(&lch_to_linear_rgb(&CIELChColor(l_mapped, c_mapped, h)))
+}
+
+/*
+Maps a linear RGB color, which may lie outside of [0; 1], into the sRGB gamut.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_map_linear_rgb(lin_rgb: &LinearRGBColor, method: GamutMappingMethod) -> LinearRGBColor {
+    if method == GamutMappingMethod::Clip || linear_rgb_in_gamut(lin_rgb, 0.0) {
+        return clip_linear_rgb(lin_rgb);
+    }
+
+    match method {
+        GamutMappingMethod::Clip => clip_linear_rgb(lin_rgb),
+        GamutMappingMethod::OklchChromaReduction => map_oklch_chroma_reduction(lin_rgb),
+        GamutMappingMethod::MinDE => map_min_de(lin_rgb)
+    }
+}
+
+/*
+Converts an XYZ color relative to the sRGB white into sRGB, mapping colors outside of
+the sRGB gamut into it with the given method.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_map_xyz_to_srgb(xyz: &CIEXYZColor, method: GamutMappingMethod) -> SRGBColor {
+    let SRGBColor(r, g, b) = linear_rgb_to_srgb(&gamut_map_linear_rgb(&xyz_to_linear_rgb(xyz), method));
+    SRGBColor(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+}
+
+/*
+Converts a CIELab color into sRGB, adapting its white onto the sRGB white first.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_map_lab_to_srgb(lab: &CIELabColor, white: &CIEXYZColor, method: GamutMappingMethod) -> SRGBColor {
+    let adaptation = ChromaticAdaptation::new(white, &SRGB_WHITE, ChromaticAdaptationMethod::default());
+    gamut_map_xyz_to_srgb(&adaptation.apply(&lab_to_xyz(lab, white)), method)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_map_oklch_to_srgb(oklch: &OklchColor, method: GamutMappingMethod) -> SRGBColor {
+    let SRGBColor(r, g, b) = linear_rgb_to_srgb(&gamut_map_linear_rgb(&oklab_to_linear_rgb(&oklch_to_oklab(oklch)), method));
+    SRGBColor(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl CIEXYZColor {
+
+    /*
+    Checks whether this color, relative to the sRGB white, can be displayed in sRGB.
+    Each linear RGB channel may exceed [0; 1] by up to tolerance.
+    */
+    pub fn is_in_srgb_gamut(&self, tolerance: f32) -> bool {
+        linear_rgb_in_gamut(&xyz_to_linear_rgb(self), tolerance)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl CIELabColor {
+
+    /*
+    Checks whether this color, relative to the given white, can be displayed in sRGB.
+    The white is adapted onto the sRGB white the same way as in the conversion kernels.
+    */
+    pub fn is_in_srgb_gamut(&self, white: &CIEXYZColor, tolerance: f32) -> bool {
+        let adaptation = ChromaticAdaptation::new(white, &SRGB_WHITE, ChromaticAdaptationMethod::default());
+        adaptation.apply(&lab_to_xyz(self, white)).is_in_srgb_gamut(tolerance)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl CIELuvColor {
+
+    /*
+    Checks whether this color, relative to the given white, can be displayed in sRGB.
+    The white is adapted onto the sRGB white the same way as in the conversion kernels.
+    */
+    pub fn is_in_srgb_gamut(&self, white: &CIEXYZColor, tolerance: f32) -> bool {
+        let adaptation = ChromaticAdaptation::new(white, &SRGB_WHITE, ChromaticAdaptationMethod::default());
+        adaptation.apply(&luv_to_xyz(self, white)).is_in_srgb_gamut(tolerance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{CompareMargin, srgb_to_xyz, xyz_to_lch};
+
+    const MARGIN: f32 = 1.0e-3;
+
+    const METHODS: [GamutMappingMethod; 3] = [GamutMappingMethod::Clip, GamutMappingMethod::OklchChromaReduction, GamutMappingMethod::MinDE];
+
+    fn in_unit_range(srgb: &SRGBColor) -> bool {
+        let &SRGBColor(r, g, b) = srgb;
+        (0.0..=1.0).contains(&r) && (0.0..=1.0).contains(&g) && (0.0..=1.0).contains(&b)
+    }
+
+    #[test]
+    fn test_in_gamut_colors_are_unchanged() {
+        for method in METHODS {
+            for &srgb in [SRGBColor(0.0, 0.5, 1.0), SRGBColor(0.2, 0.3, 0.4), SRGBColor(1.0, 1.0, 1.0)].iter() {
+                assert!(gamut_map_xyz_to_srgb(&srgb_to_xyz(&srgb), method).equal_within(srgb, MARGIN));
+            }
+        }
+    }
+
+    #[test]
+    fn test_clip() {
+        let lin_rgb = LinearRGBColor(1.2, -0.1, 0.5);
+        assert!(gamut_map_linear_rgb(&lin_rgb, GamutMappingMethod::Clip).equal_within(LinearRGBColor(1.0, 0.0, 0.5), MARGIN));
+    }
+
+    #[test]
+    fn test_oklch_chroma_reduction() {
+        let oklch = OklchColor(0.7, 0.4, 150.0);
+        let mapped = gamut_map_oklch_to_srgb(&oklch, GamutMappingMethod::OklchChromaReduction);
+        assert!(in_unit_range(&mapped));
+
+        // Lightness and hue are kept up to the final clip, which is at most one JND.
+        let lin_rgb = crate::srgb_to_linear_rgb(&mapped);
+        let mapped_oklab = linear_rgb_to_oklab(&lin_rgb);
+        let mapped_oklch = oklab_to_oklch(&mapped_oklab);
+        assert!(delta_e_ok(&mapped_oklab, &oklch_to_oklab(&OklchColor(0.7, mapped_oklch.1, 150.0))) < JND_OKLAB + 1.0e-3);
+        assert!(mapped_oklch.1 < 0.4);
+
+        assert!(gamut_map_oklch_to_srgb(&OklchColor(1.2, 0.1, 30.0), GamutMappingMethod::OklchChromaReduction).equal_within(SRGBColor(1.0, 1.0, 1.0), MARGIN));
+        assert!(gamut_map_oklch_to_srgb(&OklchColor(-0.1, 0.1, 30.0), GamutMappingMethod::OklchChromaReduction).equal_within(SRGBColor(0.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_oklch_chroma_reduction_stays_within_jnd() {
+        /*
+        The reduced chroma is never one whose clipped color is a JND or more away from it.
+        Very dark colors are left out, GAMUT_TOLERANCE is large compared to their linear RGB values.
+        */
+        for i in 10..100 {
+            for j in 0..360 {
+                let (l, h) = (i as f32 * 0.01, j as f32);
+                let chroma = reduce_chroma_oklch(l, 0.4, h);
+                let reduced = oklch_to_oklab(&OklchColor(l, chroma, h));
+                let clipped = linear_rgb_to_oklab(&clip_linear_rgb(&oklab_to_linear_rgb(&reduced)));
+                assert!(delta_e_ok(&clipped, &reduced) < JND_OKLAB, "{} {}", l, h);
+                assert!(chroma < 0.4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_de() {
+        let lab = CIELabColor(50.0, 100.0, -100.0);
+        let mapped = gamut_map_lab_to_srgb(&lab, &SRGB_WHITE, GamutMappingMethod::MinDE);
+        assert!(in_unit_range(&mapped));
+
+        let CIELChColor(_, _, h) = xyz_to_lch(&srgb_to_xyz(&mapped), &SRGB_WHITE);
+        let CIELChColor(_, _, h_original) = lab_to_lch(&lab);
+        assert!((h - h_original).abs() < 0.5);
+
+        // MINDE never ends up further away than clipping.
+        let clipped = gamut_map_lab_to_srgb(&lab, &SRGB_WHITE, GamutMappingMethod::Clip);
+        let distance = |srgb: &SRGBColor| crate::delta_e_76(&xyz_to_lab(&srgb_to_xyz(srgb), &SRGB_WHITE), &lab);
+        assert!(distance(&mapped) <= distance(&clipped) + MARGIN);
+    }
+
+    #[test]
+    fn test_is_in_srgb_gamut() {
+        assert!(CIEXYZColor(0.9505, 1.0, 1.089).is_in_srgb_gamut(1.0e-3));
+        assert!(!CIEXYZColor(0.9505, 1.0, 1.089).is_in_srgb_gamut(-1.0e-2));
+        assert!(!CIEXYZColor(0.3, 0.6, 0.05).is_in_srgb_gamut(1.0e-3));
+
+        assert!(CIELabColor(60.0, 60.0, 30.0).is_in_srgb_gamut(&SRGB_WHITE, 1.0e-3));
+        assert!(!CIELabColor(50.0, 100.0, -100.0).is_in_srgb_gamut(&SRGB_WHITE, 1.0e-3));
+        assert!(!CIELabColor(101.0, 0.0, 0.0).is_in_srgb_gamut(&SRGB_WHITE, 1.0e-3));
+
+        // Neutral colors stay neutral under any white, since the white gets adapted.
+        let d50 = CIEXYZColor(0.96422, 1.0, 0.82521);
+        assert!(CIELabColor(100.0, 0.0, 0.0).is_in_srgb_gamut(&d50, 1.0e-3));
+        assert!(CIELuvColor(100.0, 0.0, 0.0).is_in_srgb_gamut(&d50, 1.0e-3));
+
+        assert!(CIELuvColor(60.680, -53.444, 42.096).is_in_srgb_gamut(&SRGB_WHITE, 1.0e-3));
+        assert!(!CIELuvColor(50.0, -150.0, 100.0).is_in_srgb_gamut(&SRGB_WHITE, 1.0e-3));
+    }
+}
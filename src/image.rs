@@ -0,0 +1,147 @@
+use crate::error::ColorSpacesError;
+
+/*
+Conversion of rectangles inside of larger images.
+
+An image is width x height RGBA u8 pixels, whose rows start stride bytes apart. The stride may
+be larger than a row, e.g. for images that are part of a larger one. Only the pixels inside of
+the rectangle are converted, everything else in the image, including the padding between rows,
+is left untouched. This allows converting a dirty region of a canvas without copying it out first.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize
+}
+
+impl ImageRect {
+
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> ImageRect {
+        ImageRect { x, y, width, height }
+    }
+
+    // The whole image.
+    pub fn full(width: usize, height: usize) -> ImageRect {
+        ImageRect { x: 0, y: 0, width, height }
+    }
+}
+
+/*
+Checks the image and the rectangle against data and returns the part of data from the first
+pixel of the rectangle to its last one, together with the length of a row of the rectangle in
+bytes. The rows of the rectangle start every stride bytes within the returned slice.
+*/
+pub(crate) fn rect_region<'a>(data: &'a mut [u8], width: usize, height: usize, stride: usize, rect: &ImageRect) -> Result<(&'a mut [u8], usize), ColorSpacesError> {
+    let row_len = match width.checked_mul(4) {
+        Some(row_len) if row_len <= stride && stride.is_multiple_of(4) => row_len,
+        _ => return Err(ColorSpacesError::InvalidStride { width, stride })
+    };
+
+    let in_image = |start: usize, len: usize, image_len: usize| start.checked_add(len).is_some_and(|end| end <= image_len);
+    if !in_image(rect.x, rect.width, width) || !in_image(rect.y, rect.height, height) {
+        return Err(ColorSpacesError::RectOutOfBounds {
+            x: rect.x, y: rect.y, width: rect.width, height: rect.height, image_width: width, image_height: height
+        });
+    }
+
+    // The last row does not need to be padded to the full stride. An image too large for usize never fits.
+    let image_len = match height {
+        0 => 0,
+        _ => (height - 1).checked_mul(stride).and_then(|len| len.checked_add(row_len)).unwrap_or(usize::MAX)
+    };
+    if image_len > data.len() {
+        return Err(ColorSpacesError::OutOfBounds { offset: 0, len: image_len, buffer_len: data.len() });
+    }
+
+    if rect.width == 0 || rect.height == 0 {
+        return Ok((&mut data[..0], 0));
+    }
+
+    let start = rect.y * stride + rect.x * 4;
+    let end = (rect.y + rect.height - 1) * stride + (rect.x + rect.width) * 4;
+    Ok((&mut data[start..end], rect.width * 4))
+}
+
+/*
+Applies a slice conversion to the rectangle rect of an image, one row of the rectangle at a time:
+
+    convert_rect(&mut canvas, 3840, 2160, 3840 * 4, &ImageRect::new(100, 200, 640, 480), |row| {
+        convert_srgb_to_lab(row, 0.34567, 0.35850, 1.0, None)
+    })?;
+
+The result is the same as converting every pixel of the rectangle on its own.
+*/
+pub fn convert_rect<F>(data: &mut [u8], width: usize, height: usize, stride: usize, rect: &ImageRect, mut convert: F) -> Result<(), ColorSpacesError>
+    where F: FnMut(&mut [u8])
+{
+    let (region, row_len) = rect_region(data, width, height, stride, rect)?;
+    if row_len == 0 {
+        return Ok(());
+    }
+
+    for row in region.chunks_mut(stride) {
+        convert(&mut row[..row_len]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // An image of 5x4 pixels with 2 pixels of padding per row, every byte set to its index.
+    fn test_image() -> Vec<u8> {
+        (0..(4 * 7 * 4)).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn test_convert_rect() {
+        let mut data = test_image();
+        let mut rows = 0;
+        convert_rect(&mut data, 5, 4, 28, &ImageRect::new(1, 1, 3, 2), |row| {
+            assert_eq!(row.len(), 12);
+            rows += 1;
+            row.fill(255);
+        }).unwrap();
+        assert_eq!(rows, 2);
+
+        for (i, &v) in data.iter().enumerate() {
+            let (x, y) = (i % 28 / 4, i / 28);
+            let inside = (1..4).contains(&x) && (1..3).contains(&y);
+            assert_eq!(v, if inside { 255 } else { i as u8 }, "{} {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_convert_rect_edges() {
+        // The last row does not need any padding after it.
+        let mut data = test_image();
+        data.truncate(3 * 28 + 20);
+        convert_rect(&mut data, 5, 4, 28, &ImageRect::full(5, 4), |row| row.fill(0)).unwrap();
+        assert!(data.chunks(28).all(|row| row[..20].iter().all(|&v| v == 0)));
+        assert_eq!(data[20], 20);
+
+        convert_rect(&mut data, 5, 4, 28, &ImageRect::new(5, 4, 0, 0), |_| panic!("empty rectangle")).unwrap();
+        convert_rect(&mut [], 0, 0, 0, &ImageRect::full(0, 0), |_| panic!("empty image")).unwrap();
+    }
+
+    #[test]
+    fn test_convert_rect_errors() {
+        let mut data = test_image();
+        let mut convert = |width, height, stride, rect| convert_rect(&mut data, width, height, stride, &rect, |_| {});
+
+        assert_eq!(convert(5, 4, 16, ImageRect::full(5, 4)), Err(ColorSpacesError::InvalidStride { width: 5, stride: 16 }));
+        assert!(convert(5, 4, 26, ImageRect::full(5, 4)).is_err());
+        assert!(convert(usize::MAX, 1, 28, ImageRect::full(1, 1)).is_err());
+
+        assert!(convert(5, 4, 28, ImageRect::new(3, 0, 3, 1)).is_err());
+        assert!(convert(5, 4, 28, ImageRect::new(0, 4, 1, 1)).is_err());
+        assert!(convert(5, 4, 28, ImageRect::new(usize::MAX, 0, 2, 1)).is_err());
+
+        assert_eq!(convert(5, 5, 28, ImageRect::full(5, 4)), Err(ColorSpacesError::OutOfBounds { offset: 0, len: 132, buffer_len: 112 }));
+        assert!(convert(5, usize::MAX, 28, ImageRect::full(5, 4)).is_err());
+    }
+}
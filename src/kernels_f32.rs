@@ -0,0 +1,383 @@
+/*
+Buffer conversion kernels for interleaved RGBA f32 data.
+
+Unlike the u8 kernels in lib.rs, the color components are stored in the native range
+of each color space (e.g. L in [0; 100] for CIELab), so no precision is lost to quantization.
+Like the u8 kernels, every conversion works on a slice and has a convert_memory_f32_* counterpart
+for JavaScript, which converts a region of a ColorBufferF32. Offsets and lengths are given in f32
+elements, not in bytes. The alpha channel is never touched.
+*/
+
+use crate::*;
+
+#[inline(always)]
+pub(crate) fn convert_pixels_f32<F>(data: &mut [f32], convert: F)
+    where F: Fn(f32, f32, f32) -> (f32, f32, f32)
+{
+    for pixel in data.chunks_exact_mut(4) {
+        let (c0, c1, c2) = convert(pixel[0], pixel[1], pixel[2]);
+        pixel[0] = c0;
+        pixel[1] = c1;
+        pixel[2] = c2;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_xyz(buffer: &mut ColorBufferF32, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_f32_srgb_to_xyz(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_xyz(data: &mut [f32]) {
+    convert_pixels_f32(data, |r, g, b| {
+        let CIEXYZColor(x, y, z) = srgb_to_xyz(&SRGBColor(r, g, b));
+        (x, y, z)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_xyz_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_f32_xyz_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_xyz_to_srgb(data: &mut [f32], gamut_mapping: Option<GamutMappingMethod>) {
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+    convert_pixels_f32(data, |x, y, z| {
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&CIEXYZColor(x, y, z), gamut_mapping);
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_linear_rgb(buffer: &mut ColorBufferF32, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_f32_srgb_to_linear_rgb(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_linear_rgb(data: &mut [f32]) {
+    convert_pixels_f32(data, |r, g, b| {
+        let LinearRGBColor(lin_r, lin_g, lin_b) = srgb_to_linear_rgb(&SRGBColor(r, g, b));
+        (lin_r, lin_g, lin_b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_linear_rgb_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_f32_linear_rgb_to_srgb(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_f32_linear_rgb_to_srgb(data: &mut [f32]) {
+    convert_pixels_f32(data, |lin_r, lin_g, lin_b| {
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&LinearRGBColor(lin_r, lin_g, lin_b));
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_lab(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_srgb_to_lab(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_lab(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    convert_pixels_f32(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r, g, b)));
+        let CIELabColor(l, a_s, b_s) = xyz_to_lab(&xyz, &white);
+        (l, a_s, b_s)
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_lab_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_lab_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_lab_to_srgb(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_f32(data, |l, a_s, b_s| {
+        let xyz = lab_to_xyz(&CIELabColor(l, a_s, b_s), &white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_luv(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_srgb_to_luv(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_luv(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+    let (u_prime_w, v_prime_w) = calc_uv_prime(&white);
+
+    convert_pixels_f32(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r, g, b)));
+        let CIELuvColor(l, u, v) = xyz_to_luv_precomputed_white::<DefaultTransfer>(&xyz, white_yy, u_prime_w, v_prime_w);
+        (l, u, v)
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_luv_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_luv_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_luv_to_srgb(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_f32(data, |l, u, v| {
+        let xyz = luv_to_xyz_precomputed_white(&CIELuvColor(l, u, v), white_yy, u_prime_white, v_prime_white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_lch(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_srgb_to_lch(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_lch(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    convert_pixels_f32(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r, g, b)));
+        let CIELChColor(l, c, h) = xyz_to_lch(&xyz, &white);
+        (l, c, h)
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_lch_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_lch_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_lch_to_srgb(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_f32(data, |l, c, h| {
+        let xyz = lch_to_xyz(&CIELChColor(l, c, h), &white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_lchuv(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_srgb_to_lchuv(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_lchuv(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+    let (u_prime_w, v_prime_w) = calc_uv_prime(&white);
+
+    convert_pixels_f32(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r, g, b)));
+        let CIELChuvColor(l, c, h) = xyz_to_lchuv_precomputed_white(&xyz, white_yy, u_prime_w, v_prime_w);
+        (l, c, h)
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_lchuv_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_f32_lchuv_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_lchuv_to_srgb(data: &mut [f32], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_f32(data, |l, c, h| {
+        let xyz = lchuv_to_xyz_precomputed_white(&CIELChuvColor(l, c, h), white_yy, u_prime_white, v_prime_white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_oklab(buffer: &mut ColorBufferF32, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_f32_srgb_to_oklab(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_oklab(data: &mut [f32]) {
+    convert_pixels_f32(data, |r, g, b| {
+        let OklabColor(l, a_s, b_s) = linear_rgb_to_oklab(&srgb_to_linear_rgb(&SRGBColor(r, g, b)));
+        (l, a_s, b_s)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_oklab_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_f32_oklab_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_oklab_to_srgb(data: &mut [f32], gamut_mapping: Option<GamutMappingMethod>) {
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+    convert_pixels_f32(data, |l, a_s, b_s| {
+        let lin_rgb = oklab_to_linear_rgb(&OklabColor(l, a_s, b_s));
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&gamut_map_linear_rgb(&lin_rgb, gamut_mapping));
+        (r, g, b)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_srgb_to_oklch(buffer: &mut ColorBufferF32, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_f32_srgb_to_oklch(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_f32_srgb_to_oklch(data: &mut [f32]) {
+    convert_pixels_f32(data, |r, g, b| {
+        let OklchColor(l, c, h) = oklab_to_oklch(&linear_rgb_to_oklab(&srgb_to_linear_rgb(&SRGBColor(r, g, b))));
+        (l, c, h)
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_f32_oklch_to_srgb(buffer: &mut ColorBufferF32, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_f32_oklch_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_f32_oklch_to_srgb(data: &mut [f32], gamut_mapping: Option<GamutMappingMethod>) {
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+    convert_pixels_f32(data, |l, c, h| {
+        let lin_rgb = oklab_to_linear_rgb(&oklch_to_oklab(&OklchColor(l, c, h)));
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&gamut_map_linear_rgb(&lin_rgb, gamut_mapping));
+        (r, g, b)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::CompareMargin;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    const D65_2: (f32, f32, f32) = (0.31271, 0.32902, 1.0);
+
+    #[cfg(feature = "std")]
+    const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+
+    fn srgb_test_pixels_f32() -> Vec<f32> {
+        crate::srgb_test_pixels().iter().map(|&v| v as f32 / 255.0).collect()
+    }
+
+    fn assert_round_trip<F, G>(to: F, from: G)
+        where F: Fn(&mut [f32]), G: Fn(&mut [f32])
+    {
+        let original = srgb_test_pixels_f32();
+        let mut data = original.clone();
+        to(&mut data);
+        from(&mut data);
+
+        for (converted, expected) in data.chunks(4).zip(original.chunks(4)) {
+            assert!(SRGBColor(converted[0], converted[1], converted[2]).equal_within(SRGBColor(expected[0], expected[1], expected[2]), MARGIN), "{:?} {:?}", converted, expected);
+            assert_eq!(converted[3], expected[3]);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_convert_memory_f32_srgb_to_lab() {
+        let (x, y, yy) = D65_2;
+        let mut buffer = ColorBufferF32::new(8).unwrap();
+        buffer.as_mut_slice()[4..].copy_from_slice(&[0.0, 128.0 / 255.0, 1.0, 1.0]);
+        convert_memory_f32_srgb_to_lab(&mut buffer, 4, 4, x, y, yy, None).unwrap();
+        let data = buffer.as_slice();
+        let expected = xyz_to_lab(&srgb_to_xyz(&SRGBColor(0.0, 128.0 / 255.0, 1.0)), &D65);
+        assert!(CIELabColor(data[4], data[5], data[6]).equal_within(expected, 2.0e-2));
+        assert_eq!(data[7], 1.0);
+        assert_eq!(&data[..4], &[0.0; 4]);
+
+        assert!(convert_memory_f32_srgb_to_lab(&mut buffer, 0, 8, x, 0.0, yy, None).is_err());
+        assert!(convert_memory_f32_srgb_to_lab(&mut buffer, 0, 3, x, y, yy, None).is_err());
+        assert!(convert_memory_f32_srgb_to_lab(&mut buffer, 4, 8, x, y, yy, None).is_err());
+    }
+
+    #[test]
+    fn test_convert_memory_f32_round_trips() {
+        let (x, y, yy) = D65_2;
+        assert_round_trip(
+            convert_f32_srgb_to_xyz,
+            |d| convert_f32_xyz_to_srgb(d, None));
+        assert_round_trip(
+            convert_f32_srgb_to_linear_rgb,
+            convert_f32_linear_rgb_to_srgb);
+        assert_round_trip(
+            |d| convert_f32_srgb_to_lab(d, x, y, yy, None),
+            |d| convert_f32_lab_to_srgb(d, x, y, yy, None, None));
+        assert_round_trip(
+            |d| convert_f32_srgb_to_luv(d, x, y, yy, None),
+            |d| convert_f32_luv_to_srgb(d, x, y, yy, None, None));
+        assert_round_trip(
+            |d| convert_f32_srgb_to_lch(d, x, y, yy, None),
+            |d| convert_f32_lch_to_srgb(d, x, y, yy, None, None));
+        assert_round_trip(
+            |d| convert_f32_srgb_to_lchuv(d, x, y, yy, None),
+            |d| convert_f32_lchuv_to_srgb(d, x, y, yy, None, None));
+        assert_round_trip(
+            convert_f32_srgb_to_oklab,
+            |d| convert_f32_oklab_to_srgb(d, None));
+        assert_round_trip(
+            convert_f32_srgb_to_oklch,
+            |d| convert_f32_oklch_to_srgb(d, None));
+    }
+}
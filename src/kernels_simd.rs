@@ -0,0 +1,407 @@
+/*
+SIMD versions of the u8 kernels for sRGB, linear RGB, CIEXYZ, CIELab and CIELuv, used by
+the convert_memory_* kernels when the crate is built with the "simd" feature.
+
+Every function converts four pixels per iteration and returns the pixels that are left over,
+which the scalar loop of the kernel converts afterwards. The vector code performs exactly the
+same float operations in the same order as the scalar code, including the polynomial root
+approximations, so both paths produce identical bytes. The exact transfer functions have no
+vector counterpart, so the "accurate" feature disables these kernels.
+*/
+
+use crate::*;
+use crate::simd::{F32x4, U32x4};
+use crate::rgb::{LINEAR_RGB_TO_XYZ, XYZ_TO_LINEAR_RGB, CORRECTION_FACTORS_12TH_ROOT};
+use crate::cielab::CORRECTION_FACTOR_3RD_ROOT;
+use crate::chromatic_adaptation::Matrix3;
+use crate::cieluv::calc_uv_prime;
+use crate::lut::{srgb_linearization_table, SRGBEncoder};
+
+const PIXELS_BYTES: usize = 16;
+
+type Color3 = (F32x4, F32x4, F32x4);
+
+#[inline(always)]
+fn multiply_matrix_vector_x4(m: &Matrix3, v: Color3) -> Color3 {
+    let (x, y, z) = v;
+    (
+        m[0][0] * x + m[0][1] * y + m[0][2] * z,
+        m[1][0] * x + m[1][1] * y + m[1][2] * z,
+        m[2][0] * x + m[2][1] * y + m[2][2] * z
+    )
+}
+
+/*
+Vector version of the root approximation in srgb_gamma() and lab_gamma(): a polynomial
+on the mantissa, corrected by a power of two and a table entry for the remaining exponent.
+The exponent is divided in floating point, which is exact for the small range of exponents.
+*/
+#[inline(always)]
+fn approximate_root_x4(val: F32x4, coefficients: [f32; 6], degree: u32, correction_factors: &[f32]) -> F32x4 {
+    let bits = val.to_bits();
+    let exponent = bits.shr(23).and(U32x4::splat(0xff)).wrapping_sub(U32x4::splat(126));
+    let x = F32x4::from_bits(bits.and(U32x4::splat(0x807fffff)).or(U32x4::splat(0x3f000000)));
+
+    let [c5, c4, c3, c2, c1, c0] = coefficients;
+    let y = ((((x * c5 - c4) * x + c3) * x - c2) * x + c1) * x + c0;
+
+    let exponent_new = (exponent.convert_i32_to_f32() / degree as f32).trunc_to_i32();
+    let correction_shift = F32x4::from_bits(exponent_new.wrapping_add(U32x4::splat(127)).and(U32x4::splat(0xff)).shl(23));
+    let correction_index = exponent
+        .wrapping_sub(exponent_new.wrapping_mul(U32x4::splat(degree)))
+        .wrapping_add(U32x4::splat(degree - 1));
+    let correction_factor = F32x4::lookup(correction_factors, correction_index);
+    y * correction_shift * correction_factor
+}
+
+// With "gamma-lut", sRGB is encoded with the table and this is only tested.
+#[cfg_attr(feature = "gamma-lut", allow(dead_code))]
+#[inline(always)]
+fn srgb_gamma_x4(val: F32x4) -> F32x4 {
+    let coefficients = [0.06740219, 0.31414926, 0.6193915, 0.6838658, 0.51255304, 0.7986689];
+    let twelfth_root = approximate_root_x4(val, coefficients, 12, &CORRECTION_FACTORS_12TH_ROOT);
+
+    let gamma_part = twelfth_root * twelfth_root;
+    let gamma_part = gamma_part * gamma_part * twelfth_root;
+    F32x4::select(val.le(0.0031308), val * 12.92, gamma_part * 1.055 - 0.055)
+}
+
+#[inline(always)]
+fn cube_root_x4(val: F32x4) -> F32x4 {
+    let coefficients = [0.13380815, 0.6322613, 1.2763454, 1.4815698, 1.3275858, 0.37609282];
+    approximate_root_x4(val, coefficients, 3, &CORRECTION_FACTOR_3RD_ROOT)
+}
+
+#[inline(always)]
+fn lab_gamma_x4(val: F32x4) -> F32x4 {
+    F32x4::select(val.le(0.008856452), val / 0.12841855 + 0.13793103, cube_root_x4(val))
+}
+
+#[inline(always)]
+fn lab_digamma_x4(val: F32x4) -> F32x4 {
+    F32x4::select(val.le(0.20689656), (val - 0.13793103) * 0.12841855, val * val * val)
+}
+
+#[inline(always)]
+fn xyz_to_lab_x4(xyz: Color3, white: &CIEXYZColor) -> Color3 {
+    let (x, y, z) = xyz;
+    let &CIEXYZColor(xw, yw, zw) = white;
+
+    let x_gamma = lab_gamma_x4(x / xw);
+    let y_gamma = lab_gamma_x4(y / yw);
+    let z_gamma = lab_gamma_x4(z / zw);
+
+    (116.0 * y_gamma - 16.0, 500.0 * (x_gamma - y_gamma), 200.0 * (y_gamma - z_gamma))
+}
+
+#[inline(always)]
+fn lab_to_xyz_x4(lab: Color3, white: &CIEXYZColor) -> Color3 {
+    let (l, a, b) = lab;
+    let &CIEXYZColor(xw, yw, zw) = white;
+
+    let y_gamma = (l + 16.0) / 116.0;
+    let x_gamma = y_gamma + a / 500.0;
+    let z_gamma = y_gamma - b / 200.0;
+
+    (xw * lab_digamma_x4(x_gamma), yw * lab_digamma_x4(y_gamma), zw * lab_digamma_x4(z_gamma))
+}
+
+#[inline(always)]
+fn xyz_to_luv_x4(xyz: Color3, yw: f32, u_prime_w: f32, v_prime_w: f32) -> Color3 {
+    let (x, y, z) = xyz;
+    let y_scaled = y / yw;
+    let l = F32x4::select(y_scaled.le(0.008856452), 903.2963 * y_scaled, 116.0 * cube_root_x4(y_scaled) - 16.0);
+
+    let uv_scale = x + 15.0 * y + 3.0 * z;
+    let black = uv_scale.eq(0.0);
+    let u_prime = F32x4::select(black, F32x4::splat(0.0), 4.0 * x / uv_scale);
+    let v_prime = F32x4::select(black, F32x4::splat(0.0), 9.0 * y / uv_scale);
+
+    (l, 13.0 * l * (u_prime - u_prime_w), 13.0 * l * (v_prime - v_prime_w))
+}
+
+#[inline(always)]
+fn luv_to_xyz_x4(luv: Color3, yw: f32, u_prime_w: f32, v_prime_w: f32) -> Color3 {
+    let (l, u, v) = luv;
+
+    let u_prime = u / (13.0 * l) + u_prime_w;
+    let v_prime = v / (13.0 * l) + v_prime_w;
+    let temp = (l + 16.0) / 116.0;
+    let y = F32x4::select(l.le(8.0), yw * l * 0.0011070565, yw * temp * temp * temp);
+
+    let x = y * (9.0 * u_prime) / (4.0 * v_prime);
+    let z = y * (12.0 - 3.0 * u_prime - 20.0 * v_prime) / (4.0 * v_prime);
+
+    let black = l.eq(0.0);
+    let zero = F32x4::splat(0.0);
+    (F32x4::select(black, zero, x), F32x4::select(black, zero, y), F32x4::select(black, zero, z))
+}
+
+// See convert_srgb_to_xyz() for how this works.
+#[inline(always)]
+fn encode_x4(val: F32x4) -> U32x4 {
+    (val.clamp(0.0, 255.0) + 256.5).to_bits().shr(15)
+}
+
+#[inline(always)]
+fn store_channel(pixels: &mut [u8], channel: usize, values: U32x4) {
+    for (i, value) in values.to_array().iter().enumerate() {
+        pixels[i * 4 + channel] = *value as u8;
+    }
+}
+
+#[inline(always)]
+fn load_srgb(pixels: &[u8], linearization: &[f32]) -> Color3 {
+    (
+        F32x4::lookup_channel(linearization, pixels, 0),
+        F32x4::lookup_channel(linearization, pixels, 1),
+        F32x4::lookup_channel(linearization, pixels, 2)
+    )
+}
+
+// Clips linear RGB values into the gamut and stores them as sRGB, like SRGBEncoder::encode().
+#[cfg(not(feature = "gamma-lut"))]
+#[inline(always)]
+fn store_srgb(pixels: &mut [u8], _encoder: &SRGBEncoder, lin_rgb: Color3) {
+    let (r, g, b) = lin_rgb;
+    store_channel(pixels, 0, encode_x4(srgb_gamma_x4(r.clamp(0.0, 1.0)) * 255.0));
+    store_channel(pixels, 1, encode_x4(srgb_gamma_x4(g.clamp(0.0, 1.0)) * 255.0));
+    store_channel(pixels, 2, encode_x4(srgb_gamma_x4(b.clamp(0.0, 1.0)) * 255.0));
+}
+
+#[cfg(feature = "gamma-lut")]
+#[inline(always)]
+fn store_srgb(pixels: &mut [u8], encoder: &SRGBEncoder, lin_rgb: Color3) {
+    let (r, g, b) = lin_rgb;
+    let (r, g, b) = (r.clamp(0.0, 1.0).to_array(), g.clamp(0.0, 1.0).to_array(), b.clamp(0.0, 1.0).to_array());
+    for i in 0..4 {
+        let (r8, g8, b8) = encoder.encode(&LinearRGBColor(r[i], g[i], b[i]));
+        pixels[i * 4] = r8;
+        pixels[i * 4 + 1] = g8;
+        pixels[i * 4 + 2] = b8;
+    }
+}
+
+pub(crate) fn convert_srgb_to_linear_rgb_x4(data: &mut [u8]) -> &mut [u8] {
+    let linearization = srgb_linearization_table();
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let (r, g, b) = load_srgb(pixels, &linearization[..]);
+        store_channel(pixels, 0, encode_x4(r * 255.0));
+        store_channel(pixels, 1, encode_x4(g * 255.0));
+        store_channel(pixels, 2, encode_x4(b * 255.0));
+    }
+    chunks.into_remainder()
+}
+
+pub(crate) fn convert_linear_rgb_to_srgb_x4(data: &mut [u8]) -> &mut [u8] {
+    let encoder = SRGBEncoder::new();
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let r = F32x4::load_channel(pixels, 0) / 255.0;
+        let g = F32x4::load_channel(pixels, 1) / 255.0;
+        let b = F32x4::load_channel(pixels, 2) / 255.0;
+        store_srgb(pixels, &encoder, (r, g, b));
+    }
+    chunks.into_remainder()
+}
+
+pub(crate) fn convert_srgb_to_xyz_x4(data: &mut [u8]) -> &mut [u8] {
+    let linearization = srgb_linearization_table();
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let (x, y, z) = multiply_matrix_vector_x4(&LINEAR_RGB_TO_XYZ, load_srgb(pixels, &linearization[..]));
+        store_channel(pixels, 0, encode_x4(x * 269.0));
+        store_channel(pixels, 1, encode_x4(y * 255.0));
+        store_channel(pixels, 2, encode_x4(z * 235.0));
+    }
+    chunks.into_remainder()
+}
+
+// Only clipping is vectorized, the other gamut mapping methods are left to the scalar kernel.
+pub(crate) fn convert_xyz_to_srgb_x4(data: &mut [u8], gamut_mapping: GamutMappingMethod) -> &mut [u8] {
+    if gamut_mapping != GamutMappingMethod::Clip {
+        return data;
+    }
+
+    let encoder = SRGBEncoder::new();
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let x = F32x4::load_channel(pixels, 0) / 269.0;
+        let y = F32x4::load_channel(pixels, 1) / 255.0;
+        let z = F32x4::load_channel(pixels, 2) / 235.0;
+        store_srgb(pixels, &encoder, multiply_matrix_vector_x4(&XYZ_TO_LINEAR_RGB, (x, y, z)));
+    }
+    chunks.into_remainder()
+}
+
+pub(crate) fn convert_srgb_to_lab_x4<'a>(data: &'a mut [u8], white: &CIEXYZColor, adaptation: &ChromaticAdaptation) -> &'a mut [u8] {
+    let linearization = srgb_linearization_table();
+    let adaptation = adaptation.matrix();
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let xyz = multiply_matrix_vector_x4(&adaptation, multiply_matrix_vector_x4(&LINEAR_RGB_TO_XYZ, load_srgb(pixels, &linearization[..])));
+        let (l, a, b) = xyz_to_lab_x4(xyz, white);
+        store_channel(pixels, 0, encode_x4(l * 2.55));
+        store_channel(pixels, 1, encode_x4((a + 106.0) * 1.1383928));
+        store_channel(pixels, 2, encode_x4((b + 128.0) * 1.0493827));
+    }
+    chunks.into_remainder()
+}
+
+pub(crate) fn convert_lab_to_srgb_x4<'a>(data: &'a mut [u8], white: &CIEXYZColor, adaptation: &ChromaticAdaptation, gamut_mapping: GamutMappingMethod) -> &'a mut [u8] {
+    if gamut_mapping != GamutMappingMethod::Clip {
+        return data;
+    }
+
+    let encoder = SRGBEncoder::new();
+    let adaptation = adaptation.matrix();
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let l = F32x4::load_channel(pixels, 0) / 2.550;
+        let a = F32x4::load_channel(pixels, 1) / 1.1383928 - 106.0;
+        let b = F32x4::load_channel(pixels, 2) / 1.0493827 - 128.0;
+
+        let xyz = multiply_matrix_vector_x4(&adaptation, lab_to_xyz_x4((l, a, b), white));
+        store_srgb(pixels, &encoder, multiply_matrix_vector_x4(&XYZ_TO_LINEAR_RGB, xyz));
+    }
+    chunks.into_remainder()
+}
+
+pub(crate) fn convert_srgb_to_luv_x4<'a>(data: &'a mut [u8], white: &CIEXYZColor, adaptation: &ChromaticAdaptation) -> &'a mut [u8] {
+    let linearization = srgb_linearization_table();
+    let adaptation = adaptation.matrix();
+    let (u_prime_w, v_prime_w) = calc_uv_prime(white);
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let xyz = multiply_matrix_vector_x4(&adaptation, multiply_matrix_vector_x4(&LINEAR_RGB_TO_XYZ, load_srgb(pixels, &linearization[..])));
+        let (l, u, v) = xyz_to_luv_x4(xyz, white.1, u_prime_w, v_prime_w);
+        store_channel(pixels, 0, encode_x4(l * 2.55));
+        store_channel(pixels, 1, encode_x4((u + 103.0) * 0.8557047));
+        store_channel(pixels, 2, encode_x4((v + 154.0) * 0.90425533));
+    }
+    chunks.into_remainder()
+}
+
+pub(crate) fn convert_luv_to_srgb_x4<'a>(data: &'a mut [u8], white: &CIEXYZColor, adaptation: &ChromaticAdaptation, gamut_mapping: GamutMappingMethod) -> &'a mut [u8] {
+    if gamut_mapping != GamutMappingMethod::Clip {
+        return data;
+    }
+
+    let encoder = SRGBEncoder::new();
+    let adaptation = adaptation.matrix();
+    let (u_prime_w, v_prime_w) = calc_uv_prime(white);
+    let mut chunks = data.chunks_exact_mut(PIXELS_BYTES);
+    for pixels in &mut chunks {
+        let l = F32x4::load_channel(pixels, 0) / 2.55;
+        let u = F32x4::load_channel(pixels, 1) / 0.8557047 - 103.0;
+        let v = F32x4::load_channel(pixels, 2) / 0.90425533 - 154.0;
+
+        let xyz = multiply_matrix_vector_x4(&adaptation, luv_to_xyz_x4((l, u, v), white.1, u_prime_w, v_prime_w));
+        store_srgb(pixels, &encoder, multiply_matrix_vector_x4(&XYZ_TO_LINEAR_RGB, xyz));
+    }
+    chunks.into_remainder()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const D50_2: (f32, f32, f32) = (0.34567, 0.35850, 1.0);
+
+    // The shared test pixels, plus a few pixels that do not fill a group of four.
+    fn test_pixels() -> Vec<u8> {
+        let mut data = crate::srgb_test_pixels();
+        data.extend_from_slice(&[1, 2, 3, 4, 250, 128, 7, 0]);
+        data
+    }
+
+    /*
+    Runs a SIMD kernel and checks it against the scalar kernel. The scalar kernel is called
+    on every pixel on its own, which is too short for the SIMD path to kick in.
+    */
+    fn assert_matches_scalar(simd: impl Fn(&mut [u8]) -> usize, scalar: impl Fn(&mut [u8])) {
+        let mut expected = test_pixels();
+        for pixel in expected.chunks_mut(4) {
+            scalar(pixel);
+        }
+
+        let mut data = test_pixels();
+        let remaining = simd(&mut data);
+        assert_eq!(remaining, (data.len() / 4) % 4 * 4);
+
+        let converted = data.len() - remaining;
+        assert_eq!(&data[..converted], &expected[..converted]);
+        assert_eq!(&data[converted..], &test_pixels()[converted..]);
+    }
+
+    fn white_and_adaptations() -> (CIEXYZColor, ChromaticAdaptation, ChromaticAdaptation) {
+        let (x, y, yy) = D50_2;
+        let white = CIEXYZColor(x / y * yy, yy, (1.0 - x - y) / y * yy);
+        let to_white = ChromaticAdaptation::new(&SRGB_WHITE, &white, ChromaticAdaptationMethod::default());
+        let from_white = ChromaticAdaptation::new(&white, &SRGB_WHITE, ChromaticAdaptationMethod::default());
+        (white, to_white, from_white)
+    }
+
+    #[test]
+    fn test_transfer_functions_x4() {
+        for i in 0..=4000 {
+            let vals = [i as f32 / 4000.0, i as f32 / 400000.0, i as f32 * 1.7e-3, -(i as f32) / 4000.0];
+            let x4 = F32x4::from_array(vals);
+            let results = [srgb_gamma_x4(x4), lab_gamma_x4(x4), lab_digamma_x4(x4)].map(|v| v.to_array());
+            for (lane, &val) in vals.iter().enumerate() {
+                assert_eq!(results[0][lane].to_bits(), Approximate::srgb_gamma(val).to_bits(), "{}", val);
+                assert_eq!(results[1][lane].to_bits(), Approximate::lab_gamma(val).to_bits(), "{}", val);
+                assert_eq!(results[2][lane].to_bits(), lab_digamma(val).to_bits(), "{}", val);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rgb_kernels_x4() {
+        assert_matches_scalar(
+            |d| convert_srgb_to_linear_rgb_x4(d).len(),
+            convert_srgb_to_linear_rgb);
+        assert_matches_scalar(
+            |d| convert_linear_rgb_to_srgb_x4(d).len(),
+            convert_linear_rgb_to_srgb);
+        assert_matches_scalar(
+            |d| convert_srgb_to_xyz_x4(d).len(),
+            convert_srgb_to_xyz);
+        assert_matches_scalar(
+            |d| convert_xyz_to_srgb_x4(d, GamutMappingMethod::Clip).len(),
+            |d| convert_xyz_to_srgb(d, None));
+    }
+
+    #[test]
+    fn test_lab_kernels_x4() {
+        let (x, y, yy) = D50_2;
+        let (white, to_white, from_white) = white_and_adaptations();
+        assert_matches_scalar(
+            |d| convert_srgb_to_lab_x4(d, &white, &to_white).len(),
+            |d| convert_srgb_to_lab(d, x, y, yy, None));
+        assert_matches_scalar(
+            |d| convert_lab_to_srgb_x4(d, &white, &from_white, GamutMappingMethod::Clip).len(),
+            |d| convert_lab_to_srgb(d, x, y, yy, None, None));
+    }
+
+    #[test]
+    fn test_luv_kernels_x4() {
+        let (x, y, yy) = D50_2;
+        let (white, to_white, from_white) = white_and_adaptations();
+        assert_matches_scalar(
+            |d| convert_srgb_to_luv_x4(d, &white, &to_white).len(),
+            |d| convert_srgb_to_luv(d, x, y, yy, None));
+        assert_matches_scalar(
+            |d| convert_luv_to_srgb_x4(d, &white, &from_white, GamutMappingMethod::Clip).len(),
+            |d| convert_luv_to_srgb(d, x, y, yy, None, None));
+    }
+
+    #[test]
+    fn test_gamut_mapping_left_to_scalar() {
+        let mut data = test_pixels();
+        assert_eq!(convert_xyz_to_srgb_x4(&mut data, GamutMappingMethod::MinDE).len(), test_pixels().len());
+        assert_eq!(data, test_pixels());
+    }
+}
@@ -0,0 +1,485 @@
+/*
+Buffer conversion kernels for interleaved RGBA u16 data.
+
+The components use the same encodings as the u8 kernels in lib.rs, only scaled
+up by 257 so that the u8 range [0; 255] maps exactly onto the u16 range [0; 65535].
+Hues are the exception, they are spread over the full [0; 65536) range like in the u8 kernels.
+Every conversion works on a slice and has a convert_memory_u16_* counterpart for JavaScript,
+which converts a region of a ColorBufferU16. Offsets and lengths are given in u16 elements, not in bytes. The alpha channel is never touched.
+*/
+
+use crate::*;
+
+const U8_TO_U16: f32 = 257.0;
+
+// Hues map [0; 360) onto all 256 u8 values, so they have to be scaled by 256 instead of 257.
+const HUE_TO_U16: f32 = 65536.0 / 360.0;
+
+/*
+Same trick as the u8 kernels use: after adding 65536.5 the integer part of the value
+occupies the top 16 bits of the mantissa, which only leaves 7 fractional bits below it.
+*/
+#[inline(always)]
+fn encode_u16(value: f32) -> u16 {
+    (f32::to_bits(value.clamp(0.0, 65535.0) + 65536.5) >> 7) as u16
+}
+
+/*
+Hues are not clamped, [0; 360) is mapped onto the full u16 range instead. Values
+in [65535.5; 65536) end up as 131072.0 or slightly above, which wraps around to 0.
+*/
+#[inline(always)]
+fn encode_hue_u16(value: f32) -> u16 {
+    (f32::to_bits(value + 65536.5) >> 7) as u16
+}
+
+#[inline(always)]
+fn convert_pixels_u16<F>(data: &mut [u16], convert: F)
+    where F: Fn(f32, f32, f32) -> (u16, u16, u16)
+{
+    for pixel in data.chunks_exact_mut(4) {
+        let (c0, c1, c2) = convert(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        pixel[0] = c0;
+        pixel[1] = c1;
+        pixel[2] = c2;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_xyz(buffer: &mut ColorBufferU16, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_u16_srgb_to_xyz(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_xyz(data: &mut [u16]) {
+    convert_pixels_u16(data, |r, g, b| {
+        let CIEXYZColor(x, y, z) = srgb_to_xyz(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0));
+        (encode_u16(x * 269.0 * U8_TO_U16), encode_u16(y * 65535.0), encode_u16(z * 235.0 * U8_TO_U16))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_xyz_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_u16_xyz_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_xyz_to_srgb(data: &mut [u16], gamut_mapping: Option<GamutMappingMethod>) {
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+    convert_pixels_u16(data, |x, y, z| {
+        let xyz = CIEXYZColor(x / (269.0 * U8_TO_U16), y / 65535.0, z / (235.0 * U8_TO_U16));
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&xyz, gamut_mapping);
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_linear_rgb(buffer: &mut ColorBufferU16, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_u16_srgb_to_linear_rgb(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_linear_rgb(data: &mut [u16]) {
+    convert_pixels_u16(data, |r, g, b| {
+        let LinearRGBColor(lin_r, lin_g, lin_b) = srgb_to_linear_rgb(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0));
+        (encode_u16(lin_r * 65535.0), encode_u16(lin_g * 65535.0), encode_u16(lin_b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_linear_rgb_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_u16_linear_rgb_to_srgb(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_u16_linear_rgb_to_srgb(data: &mut [u16]) {
+    convert_pixels_u16(data, |lin_r, lin_g, lin_b| {
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&LinearRGBColor(lin_r / 65535.0, lin_g / 65535.0, lin_b / 65535.0));
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_lab(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_srgb_to_lab(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_lab(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    convert_pixels_u16(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0)));
+        let CIELabColor(l, a_s, b_s) = xyz_to_lab(&xyz, &white);
+        (
+            encode_u16(l * 2.55 * U8_TO_U16),
+            encode_u16((a_s + 106.0) * 1.1383928 * U8_TO_U16),
+            encode_u16((b_s + 128.0) * 1.0493827 * U8_TO_U16)
+        )
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_lab_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_lab_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_lab_to_srgb(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_u16(data, |l, a_s, b_s| {
+        let lab = CIELabColor(
+            l / (2.55 * U8_TO_U16),
+            a_s / (1.1383928 * U8_TO_U16) - 106.0,
+            b_s / (1.0493827 * U8_TO_U16) - 128.0
+        );
+        let xyz = lab_to_xyz(&lab, &white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_luv(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_srgb_to_luv(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_luv(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+    let (u_prime_w, v_prime_w) = calc_uv_prime(&white);
+
+    convert_pixels_u16(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0)));
+        let CIELuvColor(l, u, v) = xyz_to_luv_precomputed_white::<DefaultTransfer>(&xyz, white_yy, u_prime_w, v_prime_w);
+        (
+            encode_u16(l * 2.55 * U8_TO_U16),
+            encode_u16((u + 103.0) * 0.8557047 * U8_TO_U16),
+            encode_u16((v + 154.0) * 0.90425533 * U8_TO_U16)
+        )
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_luv_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_luv_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_luv_to_srgb(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_u16(data, |l, u, v| {
+        let luv = CIELuvColor(
+            l / (2.55 * U8_TO_U16),
+            u / (0.8557047 * U8_TO_U16) - 103.0,
+            v / (0.90425533 * U8_TO_U16) - 154.0
+        );
+        let xyz = luv_to_xyz_precomputed_white(&luv, white_yy, u_prime_white, v_prime_white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_lch(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_srgb_to_lch(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_lch(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    convert_pixels_u16(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0)));
+        let CIELChColor(l, c, h) = xyz_to_lch(&xyz, &white);
+        (
+            encode_u16(l * 2.55 * U8_TO_U16),
+            encode_u16(c * 1.9029851 * U8_TO_U16),
+            encode_hue_u16(h * HUE_TO_U16)
+        )
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_lch_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_lch_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_lch_to_srgb(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_u16(data, |l, c, h| {
+        let lch = CIELChColor(
+            l / (2.55 * U8_TO_U16),
+            c / (1.9029851 * U8_TO_U16),
+            h / (HUE_TO_U16)
+        );
+        let xyz = lch_to_xyz(&lch, &white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_lchuv(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_srgb_to_lchuv(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_lchuv(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+    let (u_prime_w, v_prime_w) = calc_uv_prime(&white);
+
+    convert_pixels_u16(data, |r, g, b| {
+        let xyz = adaptation.apply(&srgb_to_xyz(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0)));
+        let CIELChuvColor(l, c, h) = xyz_to_lchuv_precomputed_white(&xyz, white_yy, u_prime_w, v_prime_w);
+        (
+            encode_u16(l * 2.55 * U8_TO_U16),
+            encode_u16(c * 1.4166666 * U8_TO_U16),
+            encode_hue_u16(h * HUE_TO_U16)
+        )
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_lchuv_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_u16_lchuv_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_lchuv_to_srgb(data: &mut [u16], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+
+    convert_pixels_u16(data, |l, c, h| {
+        let lchuv = CIELChuvColor(
+            l / (2.55 * U8_TO_U16),
+            c / (1.4166666 * U8_TO_U16),
+            h / (HUE_TO_U16)
+        );
+        let xyz = lchuv_to_xyz_precomputed_white(&lchuv, white_yy, u_prime_white, v_prime_white);
+        let SRGBColor(r, g, b) = gamut_map_xyz_to_srgb(&adaptation.apply(&xyz), gamut_mapping);
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_oklab(buffer: &mut ColorBufferU16, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_u16_srgb_to_oklab(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_oklab(data: &mut [u16]) {
+    convert_pixels_u16(data, |r, g, b| {
+        let lin_rgb = srgb_to_linear_rgb(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0));
+        let OklabColor(l, a_s, b_s) = linear_rgb_to_oklab(&lin_rgb);
+        (
+            encode_u16(l * 65535.0),
+            encode_u16((a_s + 0.24) * 490.3846 * U8_TO_U16),
+            encode_u16((b_s + 0.32) * 490.3846 * U8_TO_U16)
+        )
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_oklab_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_u16_oklab_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_oklab_to_srgb(data: &mut [u16], gamut_mapping: Option<GamutMappingMethod>) {
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+    convert_pixels_u16(data, |l, a_s, b_s| {
+        let oklab = OklabColor(
+            l / 65535.0,
+            a_s / (490.3846 * U8_TO_U16) - 0.24,
+            b_s / (490.3846 * U8_TO_U16) - 0.32
+        );
+        let lin_rgb = oklab_to_linear_rgb(&oklab);
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&gamut_map_linear_rgb(&lin_rgb, gamut_mapping));
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_srgb_to_oklch(buffer: &mut ColorBufferU16, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_u16_srgb_to_oklch(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_u16_srgb_to_oklch(data: &mut [u16]) {
+    convert_pixels_u16(data, |r, g, b| {
+        let lin_rgb = srgb_to_linear_rgb(&SRGBColor(r / 65535.0, g / 65535.0, b / 65535.0));
+        let OklchColor(l, c, h) = oklab_to_oklch(&linear_rgb_to_oklab(&lin_rgb));
+        (
+            encode_u16(l * 65535.0),
+            encode_u16(c * 772.7273 * U8_TO_U16),
+            encode_hue_u16(h * HUE_TO_U16)
+        )
+    });
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_u16_oklch_to_srgb(buffer: &mut ColorBufferU16, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_u16_oklch_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_u16_oklch_to_srgb(data: &mut [u16], gamut_mapping: Option<GamutMappingMethod>) {
+    let gamut_mapping = gamut_mapping.unwrap_or_default();
+    convert_pixels_u16(data, |l, c, h| {
+        let oklch = OklchColor(
+            l / 65535.0,
+            c / (772.7273 * U8_TO_U16),
+            h / (HUE_TO_U16)
+        );
+        let lin_rgb = oklab_to_linear_rgb(&oklch_to_oklab(&oklch));
+        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&gamut_map_linear_rgb(&lin_rgb, gamut_mapping));
+        (encode_u16(r * 65535.0), encode_u16(g * 65535.0), encode_u16(b * 65535.0))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const D65_2: (f32, f32, f32) = (0.31271, 0.32902, 1.0);
+
+    fn srgb_test_pixels_u16() -> Vec<u16> {
+        crate::srgb_test_pixels().iter().map(|&v| v as u16 * 257).collect()
+    }
+
+    fn assert_round_trip<F, G>(to: F, from: G, tolerance: u16)
+        where F: Fn(&mut [u16]), G: Fn(&mut [u16])
+    {
+        let original = srgb_test_pixels_u16();
+        let mut data = original.clone();
+        to(&mut data);
+        from(&mut data);
+
+        for (converted, expected) in data.chunks(4).zip(original.chunks(4)) {
+            for c in 0..3 {
+                assert!(converted[c].abs_diff(expected[c]) <= tolerance, "{:?} {:?}", converted, expected);
+            }
+            assert_eq!(converted[3], expected[3]);
+        }
+    }
+
+    #[test]
+    fn test_encode_u16() {
+        assert_eq!(encode_u16(-3.0), 0);
+        assert_eq!(encode_u16(1234.4), 1234);
+        assert_eq!(encode_u16(1234.6), 1235);
+        assert_eq!(encode_u16(70000.0), 65535);
+        assert_eq!(encode_hue_u16(65535.4), 65535);
+        assert_eq!(encode_hue_u16(65535.6), 0);
+    }
+
+    #[test]
+    fn test_convert_memory_u16_srgb_to_lab() {
+        let (x, y, yy) = D65_2;
+        let mut data = vec![65535, 65535, 65535, 7];
+        convert_u16_srgb_to_lab(&mut data, x, y, yy, None);
+        // White sits right between two steps of b, so exact and approximate transfer functions may round differently.
+        let expected = [65535, (106.0 * 1.1383928 * U8_TO_U16).round() as u16, (128.0 * 1.0493827 * U8_TO_U16).round() as u16, 7];
+        for (c, e) in data.iter().zip(expected.iter()) {
+            assert!(c.abs_diff(*e) <= 1, "{:?} {:?}", data, expected);
+        }
+    }
+
+    /*
+    The round trips of the u8 kernels lose several steps for dark colors. With 16 bits, they
+    stay within a tiny fraction of a u8 step for the whole sRGB gamut.
+    */
+    #[test]
+    fn test_convert_memory_u16_round_trips() {
+        let (x, y, yy) = D65_2;
+        assert_round_trip(
+            |d| convert_u16_srgb_to_lab(d, x, y, yy, None),
+            |d| convert_u16_lab_to_srgb(d, x, y, yy, None, None), 64);
+        assert_round_trip(
+            |d| convert_u16_srgb_to_luv(d, x, y, yy, None),
+            |d| convert_u16_luv_to_srgb(d, x, y, yy, None, None), 64);
+        assert_round_trip(
+            |d| convert_u16_srgb_to_lch(d, x, y, yy, None),
+            |d| convert_u16_lch_to_srgb(d, x, y, yy, None, None), 64);
+        assert_round_trip(
+            |d| convert_u16_srgb_to_lchuv(d, x, y, yy, None),
+            |d| convert_u16_lchuv_to_srgb(d, x, y, yy, None, None), 64);
+        assert_round_trip(
+            convert_u16_srgb_to_oklab,
+            |d| convert_u16_oklab_to_srgb(d, None), 64);
+        assert_round_trip(
+            convert_u16_srgb_to_oklch,
+            |d| convert_u16_oklch_to_srgb(d, None), 64);
+    }
+
+    #[test]
+    fn test_convert_memory_u16_xyz_round_trip() {
+        // The XYZ encoding saturates X slightly below the white point, just like the u8 one does.
+        let original: Vec<u16> = srgb_test_pixels_u16().iter().map(|&v| (v as u32 * 9 / 10) as u16).collect();
+        let mut data = original.clone();
+        convert_u16_srgb_to_xyz(&mut data);
+        convert_u16_xyz_to_srgb(&mut data, None);
+        for (converted, expected) in data.iter().zip(original.iter()) {
+            assert!(converted.abs_diff(*expected) <= 64);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_u16_linear_rgb_round_trip() {
+        // Linear RGB needs more precision than 16 bits near black, so only the brighter half is checked tightly.
+        let original: Vec<u16> = (32768..=65535).step_by(97).flat_map(|v| [v as u16, v as u16, v as u16, 0]).collect();
+        let mut data = original.clone();
+        convert_u16_srgb_to_linear_rgb(&mut data);
+        convert_u16_linear_rgb_to_srgb(&mut data);
+        for (converted, expected) in data.iter().zip(original.iter()) {
+            assert!(converted.abs_diff(*expected) <= 64);
+        }
+    }
+}
@@ -1,14 +1,63 @@
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
 mod rgb;
 mod cielab;
+mod cielch;
 mod cieluv;
+mod cielchuv;
+mod oklab;
+mod oklch;
+mod chromatic_adaptation;
+mod delta_e;
+mod gamut_mapping;
+mod color_space;
+mod pipeline;
+mod kernels_f32;
+mod kernels_u16;
+mod colors_f64;
+mod transfer;
+mod lut;
+mod error;
+mod image;
+#[cfg(feature = "std")]
+mod buffer;
+#[cfg(feature = "parallel")]
+mod thread_pool;
+#[cfg(all(any(feature = "simd", test), not(feature = "accurate")))]
+mod simd;
+#[cfg(all(any(feature = "simd", test), not(feature = "accurate")))]
+mod kernels_simd;
 
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
-use js_sys::Uint8Array;
-use std::alloc::{alloc, dealloc, Layout};
 
 pub use crate::rgb::*;
 pub use crate::cielab::*;
+pub use crate::cielch::*;
 pub use crate::cieluv::*;
+pub use crate::cielchuv::*;
+pub use crate::oklab::*;
+pub use crate::oklch::*;
+pub use crate::chromatic_adaptation::*;
+pub use crate::delta_e::*;
+pub use crate::gamut_mapping::*;
+pub use crate::color_space::*;
+pub use crate::pipeline::*;
+pub use crate::kernels_f32::*;
+pub use crate::kernels_u16::*;
+pub use crate::colors_f64::*;
+pub use crate::transfer::*;
+pub use crate::error::ColorSpacesError;
+pub use crate::image::{ImageRect, convert_rect};
+#[cfg(feature = "std")]
+pub use crate::buffer::*;
+#[cfg(feature = "parallel")]
+pub use crate::thread_pool::*;
+use crate::lut::{srgb_linearization_table, SRGBEncoder};
+#[cfg(feature = "std")]
+use crate::error::{check_white_point, check_pixels};
+#[cfg(all(feature = "simd", not(feature = "accurate")))]
+use crate::kernels_simd::*;
 
 pub trait TristimulusColor {
     fn get_0(&self) -> f32;
@@ -16,10 +65,12 @@ pub trait TristimulusColor {
     fn get_2(&self) -> f32;
 }
 
+#[cfg(test)]
 pub(crate) trait CompareMargin<T> {
     fn equal_within(&self, other: T, margin: f32) -> bool;
 }
 
+#[cfg(test)]
 impl<T: TristimulusColor> CompareMargin<T> for T {
 
     fn equal_within(&self, other: T, margin: f32) -> bool {
@@ -29,41 +80,50 @@ impl<T: TristimulusColor> CompareMargin<T> for T {
     }
 }
 
-#[wasm_bindgen]
-pub fn allocate_buffer(len: usize) -> *mut u8 {
-    let layout = Layout::array::<u8>(len).unwrap();
-    unsafe {
-        alloc(layout)
+/*
+Pixels for the tests of the conversion kernels: every 17th value of each channel, which covers
+the corners of the sRGB cube. The alpha value is one that no conversion should change.
+*/
+#[cfg(test)]
+pub(crate) fn srgb_test_pixels() -> Vec<u8> {
+    let mut data = Vec::new();
+    for r in (0..=255).step_by(17) {
+        for g in (0..=255).step_by(17) {
+            for b in (0..=255).step_by(17) {
+                data.extend_from_slice(&[r as u8, g as u8, b as u8, 77]);
+            }
+        }
     }
+    data
 }
 
-#[wasm_bindgen]
-pub fn deallocate_buffer(ptr: *mut u8, len: usize) {
-    let layout = Layout::array::<u8>(len).unwrap();
-    unsafe {
-        dealloc(ptr, layout);
-    }
-}
+/*
+Conversion kernels for interleaved RGBA u8 data. Every conversion works on a slice of bytes,
+and has a convert_memory_* counterpart for JavaScript, which converts the region of a ColorBuffer
+given by an offset and a length in bytes. The alpha channel is never touched.
+*/
 
-#[wasm_bindgen]
-pub fn get_memory_view(ptr: *mut u8, len: usize) -> Uint8Array {
-    unsafe {
-        Uint8Array::view_mut_raw(ptr, len)
-    }
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_xyz(buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_srgb_to_xyz(buffer.region(offset, len)?);
+    Ok(())
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_srgb_to_xyz(ptr: *mut u8, offset: usize, len: usize) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+pub fn convert_srgb_to_xyz(data: &mut [u8]) {
+    let linearization = srgb_linearization_table();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_srgb_to_xyz_x4(data);
 
     for i in 0..(data.len() / 4) {
-        let r = data[i * 4 + 0] as f32 / 255.0;
-        let g = data[i * 4 + 1] as f32 / 255.0;
-        let b = data[i * 4 + 2] as f32 / 255.0;
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
 
-        let CIEXYZColor(x, y, z) = srgb_to_xyz(&SRGBColor(r, g, b));
+        let CIEXYZColor(x, y, z) = linear_rgb_to_xyz(&lin_rgb);
 
         /*
         This cast from f32 to u8 looks strange but has very good performance.
@@ -75,155 +135,976 @@ pub fn convert_memory_srgb_to_xyz(ptr: *mut u8, offset: usize, len: usize) {
         start with the equivalent integer value. Finally, the top 8 bits of the 
         fractional part are extracted to form the final u8 value.
         */
-        data[i * 4 + 0] = (f32::to_bits((x * 269.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = (f32::to_bits((x * 269.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
         data[i * 4 + 1] = (f32::to_bits((y * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
         data[i * 4 + 2] = (f32::to_bits((z * 235.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_xyz_to_srgb(ptr: *mut u8, offset: usize, len: usize) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_xyz_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_xyz_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_xyz_to_srgb(data: &mut [u8], gamut_mapping: Option<GamutMappingMethod>) {
+    let encoder = SRGBEncoder::new();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_xyz_to_srgb_x4(data, gamut_mapping.unwrap_or_default());
 
     for i in 0..(data.len() / 4) {
-        let x = data[i * 4 + 0] as f32 / 269.0;
+        let x = data[i * 4] as f32 / 269.0;
         let y = data[i * 4 + 1] as f32 / 255.0;
         let z = data[i * 4 + 2] as f32 / 235.0;
 
-        let SRGBColor(r, g, b) = xyz_to_srgb(&CIEXYZColor(x, y, z));
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&xyz_to_linear_rgb(&CIEXYZColor(x, y, z)), gamut_mapping.unwrap_or_default()));
 
-        data[i * 4 + 0] = (f32::to_bits((r * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 1] = (f32::to_bits((g * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 2] = (f32::to_bits((b * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_srgb_to_linear_rgb(ptr: *mut u8, offset: usize, len: usize) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_linear_rgb(buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_srgb_to_linear_rgb(buffer.region(offset, len)?);
+    Ok(())
+}
 
-    for i in 0..(data.len() / 4) {
-        let r = data[i * 4 + 0] as f32 / 255.0;
-        let g = data[i * 4 + 1] as f32 / 255.0;
-        let b = data[i * 4 + 2] as f32 / 255.0;
+pub fn convert_srgb_to_linear_rgb(data: &mut [u8]) {
+    let linearization = srgb_linearization_table();
 
-        let LinearRGBColor(lin_r, lin_g, lin_b) = srgb_to_linear_rgb(&SRGBColor(r, g, b));
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_srgb_to_linear_rgb_x4(data);
+
+    for i in 0..(data.len() / 4) {
+        let lin_r = linearization[data[i * 4] as usize];
+        let lin_g = linearization[data[i * 4 + 1] as usize];
+        let lin_b = linearization[data[i * 4 + 2] as usize];
 
-        data[i * 4 + 0] = (f32::to_bits((lin_r * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = (f32::to_bits((lin_r * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
         data[i * 4 + 1] = (f32::to_bits((lin_g * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
         data[i * 4 + 2] = (f32::to_bits((lin_b * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_linear_rgb_to_srgb(ptr: *mut u8, offset: usize, len: usize) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_linear_rgb_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_linear_rgb_to_srgb(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_linear_rgb_to_srgb(data: &mut [u8]) {
+    let encoder = SRGBEncoder::new();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_linear_rgb_to_srgb_x4(data);
 
     for i in 0..(data.len() / 4) {
-        let lin_r = data[i * 4 + 0] as f32 / 255.0;
+        let lin_r = data[i * 4] as f32 / 255.0;
         let lin_g = data[i * 4 + 1] as f32 / 255.0;
         let lin_b = data[i * 4 + 2] as f32 / 255.0;
 
-        let SRGBColor(r, g, b) = linear_rgb_to_srgb(&LinearRGBColor(lin_r, lin_g, lin_b));
+        let (r, g, b) = encoder.encode(&LinearRGBColor(lin_r, lin_g, lin_b));
 
-        data[i * 4 + 0] = (f32::to_bits((r * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 1] = (f32::to_bits((g * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 2] = (f32::to_bits((b * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_srgb_to_lab(ptr: *mut u8, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_lab(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_srgb_to_lab(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
 
+pub fn convert_srgb_to_lab(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
     let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    let linearization = srgb_linearization_table();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_srgb_to_lab_x4(data, &white, &adaptation);
 
     for i in 0..(data.len() / 4) {
-        let r = data[i * 4 + 0] as f32 / 255.0;
-        let g = data[i * 4 + 1] as f32 / 255.0;
-        let b = data[i * 4 + 2] as f32 / 255.0;
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
 
-        let xyz = srgb_to_xyz(&SRGBColor(r, g, b));
+        let xyz = adaptation.apply(&linear_rgb_to_xyz(&lin_rgb));
         let CIELabColor(l, a_s, b_s) = xyz_to_lab(&xyz, &white);
 
-        data[i * 4 + 0] = (f32::to_bits((l * 2.55).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 1] = (f32::to_bits(((a_s + 106.0) * 1.138392857).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 2] = (f32::to_bits(((b_s + 128.0) * 1.049382716).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = (f32::to_bits((l * 2.55).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 1] = (f32::to_bits(((a_s + 106.0) * 1.1383928).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 2] = (f32::to_bits(((b_s + 128.0) * 1.0493827).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_lab_to_srgb(ptr: *mut u8, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_lab_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_lab_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
 
+pub fn convert_lab_to_srgb(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
     let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+
+    let encoder = SRGBEncoder::new();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_lab_to_srgb_x4(data, &white, &adaptation, gamut_mapping.unwrap_or_default());
 
     for i in 0..(data.len() / 4) {
-        let l = data[i * 4 + 0] as f32 / 2.550;
-        let a_s = data[i * 4 + 1] as f32 / 1.138392857 - 106.0;
-        let b_s = data[i * 4 + 2] as f32 / 1.049382716 - 128.0;
+        let l = data[i * 4] as f32 / 2.550;
+        let a_s = data[i * 4 + 1] as f32 / 1.1383928 - 106.0;
+        let b_s = data[i * 4 + 2] as f32 / 1.0493827 - 128.0;
 
         let xyz = lab_to_xyz(&CIELabColor(l, a_s, b_s), &white);
-        let SRGBColor(r, g, b) = xyz_to_srgb(&xyz);
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&xyz_to_linear_rgb(&adaptation.apply(&xyz)), gamut_mapping.unwrap_or_default()));
 
-        data[i * 4 + 0] = (f32::to_bits((r * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 1] = (f32::to_bits((g * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 2] = (f32::to_bits((b * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_srgb_to_luv(ptr: *mut u8, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_luv(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_srgb_to_luv(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
 
+pub fn convert_srgb_to_luv(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
     let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
     let (u_prime_w, v_prime_w) = calc_uv_prime(&white);
 
+    let linearization = srgb_linearization_table();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_srgb_to_luv_x4(data, &white, &adaptation);
+
     for i in 0..(data.len() / 4) {
-        let r = data[i * 4 + 0] as f32 / 255.0;
-        let g = data[i * 4 + 1] as f32 / 255.0;
-        let b = data[i * 4 + 2] as f32 / 255.0;
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
 
-        let xyz = srgb_to_xyz(&SRGBColor(r, g, b));
-        let CIELuvColor(l, u, v) = xyz_to_luv_precomputed_white(&xyz, white_yy, u_prime_w, v_prime_w);
+        let xyz = adaptation.apply(&linear_rgb_to_xyz(&lin_rgb));
+        let CIELuvColor(l, u, v) = xyz_to_luv_precomputed_white::<DefaultTransfer>(&xyz, white_yy, u_prime_w, v_prime_w);
 
-        data[i * 4 + 0] = (f32::to_bits((l * 2.55).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 1] = (f32::to_bits(((u + 103.0) * 0.855704698).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 2] = (f32::to_bits(((v + 154.0) * 0.9042553191).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4] = (f32::to_bits((l * 2.55).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 1] = (f32::to_bits(((u + 103.0) * 0.8557047).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 2] = (f32::to_bits(((v + 154.0) * 0.90425533).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
     }
 }
 
-#[wasm_bindgen]
-pub fn convert_memory_luv_to_srgb(ptr: *mut u8, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32) {
-    let data: &mut [u8] = unsafe {
-        std::slice::from_raw_parts_mut(ptr.add(offset), len)
-    };
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_luv_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_luv_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
 
+pub fn convert_luv_to_srgb(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
     let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
     let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
 
+    let encoder = SRGBEncoder::new();
+
+    #[cfg(all(feature = "simd", not(feature = "accurate")))]
+    let data = convert_luv_to_srgb_x4(data, &white, &adaptation, gamut_mapping.unwrap_or_default());
+
     for i in 0..(data.len() / 4) {
-        let l = data[i * 4 + 0] as f32 / 2.55;
-        let u = data[i * 4 + 1] as f32 / 0.855704698 - 103.0;
-        let v = data[i * 4 + 2] as f32 / 0.9042553191 - 154.0;
+        let l = data[i * 4] as f32 / 2.55;
+        let u = data[i * 4 + 1] as f32 / 0.8557047 - 103.0;
+        let v = data[i * 4 + 2] as f32 / 0.90425533 - 154.0;
 
         let xyz = luv_to_xyz_precomputed_white(&CIELuvColor(l, u, v), white_yy, u_prime_white, v_prime_white);
-        let SRGBColor(r, g, b) = xyz_to_srgb(&xyz);
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&xyz_to_linear_rgb(&adaptation.apply(&xyz)), gamut_mapping.unwrap_or_default()));
+
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_lch(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_srgb_to_lch(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_srgb_to_lch(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    let linearization = srgb_linearization_table();
+
+    for i in 0..(data.len() / 4) {
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
+
+        let xyz = adaptation.apply(&linear_rgb_to_xyz(&lin_rgb));
+        let CIELChColor(l, c, h) = xyz_to_lch(&xyz, &white);
+
+        /*
+        The hue is not clamped, because it is an angle. Instead, the range [0; 360) is mapped
+        onto the full 256 byte values, so that hues close to 360 wrap around to 0 instead of
+        saturating at 255. The float cast takes care of this on its own: values in [255.5; 256)
+        end up as 512.0 or slightly above after the offset, whose top 8 fractional bits are all zero.
+        */
+        data[i * 4] = (f32::to_bits((l * 2.55).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 1] = (f32::to_bits((c * 1.9029851).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 2] = (f32::to_bits(h * 0.7111111 + 256.5) >> 15) as u8;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_lch_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_lch_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_lch_to_srgb(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+
+    let encoder = SRGBEncoder::new();
+
+    for i in 0..(data.len() / 4) {
+        let l = data[i * 4] as f32 / 2.55;
+        let c = data[i * 4 + 1] as f32 / 1.9029851;
+        let h = data[i * 4 + 2] as f32 / 0.7111111;
+
+        let xyz = lch_to_xyz(&CIELChColor(l, c, h), &white);
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&xyz_to_linear_rgb(&adaptation.apply(&xyz)), gamut_mapping.unwrap_or_default()));
+
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_lchuv(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_srgb_to_lchuv(buffer.region(offset, len)?, white_x, white_y, white_yy, method);
+    Ok(())
+}
+
+pub fn convert_srgb_to_lchuv(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+    let (u_prime_w, v_prime_w) = calc_uv_prime(&white);
+
+    let linearization = srgb_linearization_table();
+
+    for i in 0..(data.len() / 4) {
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
+
+        let xyz = adaptation.apply(&linear_rgb_to_xyz(&lin_rgb));
+        let CIELChuvColor(l, c, h) = xyz_to_lchuv_precomputed_white(&xyz, white_yy, u_prime_w, v_prime_w);
+
+        // See convert_srgb_to_lch() for how the hue wraps around.
+        data[i * 4] = (f32::to_bits((l * 2.55).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 1] = (f32::to_bits((c * 1.4166666).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 2] = (f32::to_bits(h * 0.7111111 + 256.5) >> 15) as u8;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_lchuv_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    convert_lchuv_to_srgb(buffer.region(offset, len)?, white_x, white_y, white_yy, method, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_lchuv_to_srgb(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>) {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
+
+    let encoder = SRGBEncoder::new();
+
+    for i in 0..(data.len() / 4) {
+        let l = data[i * 4] as f32 / 2.55;
+        let c = data[i * 4 + 1] as f32 / 1.4166666;
+        let h = data[i * 4 + 2] as f32 / 0.7111111;
+
+        let xyz = lchuv_to_xyz_precomputed_white(&CIELChuvColor(l, c, h), white_yy, u_prime_white, v_prime_white);
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&xyz_to_linear_rgb(&adaptation.apply(&xyz)), gamut_mapping.unwrap_or_default()));
+
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_oklab(buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_srgb_to_oklab(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_srgb_to_oklab(data: &mut [u8]) {
+    let linearization = srgb_linearization_table();
+
+    for i in 0..(data.len() / 4) {
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
+
+        let OklabColor(l, a_s, b_s) = linear_rgb_to_oklab(&lin_rgb);
+
+        data[i * 4] = (f32::to_bits((l * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 1] = (f32::to_bits(((a_s + 0.24) * 490.3846).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 2] = (f32::to_bits(((b_s + 0.32) * 490.3846).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_oklab_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_oklab_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_oklab_to_srgb(data: &mut [u8], gamut_mapping: Option<GamutMappingMethod>) {
+    let encoder = SRGBEncoder::new();
+
+    for i in 0..(data.len() / 4) {
+        let l = data[i * 4] as f32 / 255.0;
+        let a_s = data[i * 4 + 1] as f32 / 490.3846 - 0.24;
+        let b_s = data[i * 4 + 2] as f32 / 490.3846 - 0.32;
+
+        let lin_rgb = oklab_to_linear_rgb(&OklabColor(l, a_s, b_s));
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&lin_rgb, gamut_mapping.unwrap_or_default()));
+
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_srgb_to_oklch(buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+    convert_srgb_to_oklch(buffer.region(offset, len)?);
+    Ok(())
+}
+
+pub fn convert_srgb_to_oklch(data: &mut [u8]) {
+    let linearization = srgb_linearization_table();
+
+    for i in 0..(data.len() / 4) {
+        let lin_rgb = LinearRGBColor(
+            linearization[data[i * 4] as usize],
+            linearization[data[i * 4 + 1] as usize],
+            linearization[data[i * 4 + 2] as usize]
+        );
+
+        let OklchColor(l, c, h) = oklab_to_oklch(&linear_rgb_to_oklab(&lin_rgb));
+
+        // See convert_srgb_to_lch() for how the hue wraps around.
+        data[i * 4] = (f32::to_bits((l * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 1] = (f32::to_bits((c * 772.7273).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data[i * 4 + 2] = (f32::to_bits(h * 0.7111111 + 256.5) >> 15) as u8;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn convert_memory_oklch_to_srgb(buffer: &mut ColorBuffer, offset: usize, len: usize, gamut_mapping: Option<GamutMappingMethod>) -> Result<(), ColorSpacesError> {
+    convert_oklch_to_srgb(buffer.region(offset, len)?, gamut_mapping);
+    Ok(())
+}
+
+pub fn convert_oklch_to_srgb(data: &mut [u8], gamut_mapping: Option<GamutMappingMethod>) {
+    let encoder = SRGBEncoder::new();
+
+    for i in 0..(data.len() / 4) {
+        let l = data[i * 4] as f32 / 255.0;
+        let c = data[i * 4 + 1] as f32 / 772.7273;
+        let h = data[i * 4 + 2] as f32 / 0.7111111;
+
+        let lin_rgb = oklab_to_linear_rgb(&oklch_to_oklab(&OklchColor(l, c, h)));
+        let (r, g, b) = encoder.encode(&gamut_map_linear_rgb(&lin_rgb, gamut_mapping.unwrap_or_default()));
+
+        data[i * 4] = r;
+        data[i * 4 + 1] = g;
+        data[i * 4 + 2] = b;
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DifferenceMapMode {
+    Grayscale = 0,
+    Heatmap = 1
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct DeltaEStatistics {
+    pub mean: f32,
+    pub max: f32,
+    pub percentile_95: f32
+}
+
+#[cfg(feature = "std")]
+const HEATMAP_STOPS: [[f32; 3]; 6] = [
+    [0.0, 0.0, 0.0],
+    [0.0, 0.0, 255.0],
+    [0.0, 255.0, 255.0],
+    [0.0, 255.0, 0.0],
+    [255.0, 255.0, 0.0],
+    [255.0, 0.0, 0.0]
+];
+
+/*
+Maps a value in [0; 1] onto a black-blue-cyan-green-yellow-red gradient.
+*/
+#[cfg(feature = "std")]
+fn heatmap_color(t: f32) -> [f32; 3] {
+    let position = t.clamp(0.0, 1.0) * (HEATMAP_STOPS.len() - 1) as f32;
+    let index = (position as usize).min(HEATMAP_STOPS.len() - 2);
+    let fraction = position - index as f32;
+    let lower = HEATMAP_STOPS[index];
+    let upper = HEATMAP_STOPS[index + 1];
+    [
+        lower[0] + (upper[0] - lower[0]) * fraction,
+        lower[1] + (upper[1] - lower[1]) * fraction,
+        lower[2] + (upper[2] - lower[2]) * fraction
+    ]
+}
+
+/*
+Compares two sRGB regions of the same length pixel by pixel using CIEDE2000. The pixels are
+converted to CIELab the same way as in convert_memory_srgb_to_lab().
+
+The difference of each pixel is written into the output region, either as a gray value or as a
+heatmap color, where max_delta_e (and everything above it) maps to white or red respectively.
+The output region must not overlap either of the input regions.
+*/
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compare_memory_srgb_delta_e(buffer: &mut ColorBuffer, offset_a: usize, offset_b: usize, offset_out: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, mode: DifferenceMapMode, max_delta_e: f32) -> Result<DeltaEStatistics, ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    check_pixels(offset_a, len)?;
+    check_pixels(offset_b, len)?;
+    check_pixels(offset_out, len)?;
+    let (data_out, [data_a, data_b]) = buffer.region_with_inputs(offset_out, len, [(offset_a, len), (offset_b, len)])?;
+    compare_srgb_delta_e(data_a, data_b, data_out, white_x, white_y, white_yy, method, mode, max_delta_e)
+}
+
+// All three slices have to be of the same length and max_delta_e has to be positive.
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+pub fn compare_srgb_delta_e(data_a: &[u8], data_b: &[u8], data_out: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, mode: DifferenceMapMode, max_delta_e: f32) -> Result<DeltaEStatistics, ColorSpacesError> {
+    if !(max_delta_e > 0.0 && max_delta_e.is_finite()) {
+        return Err(ColorSpacesError::InvalidMaxDeltaE { max_delta_e });
+    }
+    for other_len in [data_a.len(), data_b.len()] {
+        if other_len != data_out.len() {
+            return Err(ColorSpacesError::LengthMismatch { len: data_out.len(), other_len });
+        }
+    }
+
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method.unwrap_or_default());
+
+    let num_pixels = data_out.len() / 4;
+    let mut differences: Vec<f32> = Vec::with_capacity(num_pixels);
+    let mut sum = 0.0_f64;
+    let mut max = 0.0_f32;
+
+    for i in 0..num_pixels {
+        let srgb_a = SRGBColor(data_a[i * 4] as f32 / 255.0, data_a[i * 4 + 1] as f32 / 255.0, data_a[i * 4 + 2] as f32 / 255.0);
+        let srgb_b = SRGBColor(data_b[i * 4] as f32 / 255.0, data_b[i * 4 + 1] as f32 / 255.0, data_b[i * 4 + 2] as f32 / 255.0);
+
+        let lab_a = xyz_to_lab(&adaptation.apply(&srgb_to_xyz(&srgb_a)), &white);
+        let lab_b = xyz_to_lab(&adaptation.apply(&srgb_to_xyz(&srgb_b)), &white);
+        let delta_e = delta_e_2000(&lab_a, &lab_b);
+
+        differences.push(delta_e);
+        sum += delta_e as f64;
+        max = max.max(delta_e);
+
+        let t = delta_e / max_delta_e;
+        let [r, g, b] = match mode {
+            DifferenceMapMode::Grayscale => [t * 255.0, t * 255.0, t * 255.0],
+            DifferenceMapMode::Heatmap => heatmap_color(t)
+        };
+
+        data_out[i * 4] = (f32::to_bits(r.clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data_out[i * 4 + 1] = (f32::to_bits(g.clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data_out[i * 4 + 2] = (f32::to_bits(b.clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        data_out[i * 4 + 3] = 255;
+    }
+
+    if differences.is_empty() {
+        return Ok(DeltaEStatistics { mean: 0.0, max: 0.0, percentile_95: 0.0 });
+    }
+
+    // Nearest-rank percentile
+    let rank = ((differences.len() as f64 * 0.95).ceil() as usize).max(1) - 1;
+    let (_, percentile_95, _) = differences.select_nth_unstable_by(rank, f32::total_cmp);
+
+    Ok(DeltaEStatistics {
+        mean: (sum / num_pixels as f64) as f32,
+        max,
+        percentile_95: *percentile_95
+    })
+}
+
+/*
+Marks the pixels of a CIELab encoded region which lie outside of sRGB.
+
+Out-of-gamut pixels are marked with 255, all others with 0. If mask_offset is given, one mask byte
+per pixel is written into the region starting there. Otherwise the mask replaces the alpha channel.
+*/
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_mask_memory_lab(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32, mask_offset: Option<usize>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    match mask_offset {
+        Some(mask_offset) => {
+            check_pixels(offset, len)?;
+            let (mask, [data]) = buffer.region_with_inputs(mask_offset, len / 4, [(offset, len)])?;
+            gamut_mask_lab(data, mask, white_x, white_y, white_yy, method, tolerance);
+        },
+        None => gamut_mask_lab_alpha(buffer.region(offset, len)?, white_x, white_y, white_yy, method, tolerance)
+    }
+    Ok(())
+}
+
+fn lab_gamut_check(white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32) -> impl Fn(&[u8]) -> u8 {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+
+    move |pixel| {
+        let l = pixel[0] as f32 / 2.550;
+        let a_s = pixel[1] as f32 / 1.1383928 - 106.0;
+        let b_s = pixel[2] as f32 / 1.0493827 - 128.0;
+
+        let xyz = adaptation.apply(&lab_to_xyz(&CIELabColor(l, a_s, b_s), &white));
+        if xyz.is_in_srgb_gamut(tolerance) { 0 } else { 255 }
+    }
+}
+
+// Writes one mask byte per pixel of data into mask.
+pub fn gamut_mask_lab(data: &[u8], mask: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32) {
+    let check = lab_gamut_check(white_x, white_y, white_yy, method, tolerance);
+    for (pixel, value) in data.chunks_exact(4).zip(mask.iter_mut()) {
+        *value = check(pixel);
+    }
+}
+
+// Writes the mask into the alpha channel.
+pub fn gamut_mask_lab_alpha(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32) {
+    let check = lab_gamut_check(white_x, white_y, white_yy, method, tolerance);
+    for pixel in data.chunks_exact_mut(4) {
+        pixel[3] = check(pixel);
+    }
+}
+
+/*
+Marks the pixels of a CIELuv encoded region which lie outside of sRGB.
+See gamut_mask_memory_lab() for the output format.
+*/
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn gamut_mask_memory_luv(buffer: &mut ColorBuffer, offset: usize, len: usize, white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32, mask_offset: Option<usize>) -> Result<(), ColorSpacesError> {
+    check_white_point(white_x, white_y, white_yy)?;
+    match mask_offset {
+        Some(mask_offset) => {
+            check_pixels(offset, len)?;
+            let (mask, [data]) = buffer.region_with_inputs(mask_offset, len / 4, [(offset, len)])?;
+            gamut_mask_luv(data, mask, white_x, white_y, white_yy, method, tolerance);
+        },
+        None => gamut_mask_luv_alpha(buffer.region(offset, len)?, white_x, white_y, white_yy, method, tolerance)
+    }
+    Ok(())
+}
+
+fn luv_gamut_check(white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32) -> impl Fn(&[u8]) -> u8 {
+    let white = CIEXYZColor(white_x / white_y * white_yy, white_yy, (1.0 - white_x - white_y) / white_y * white_yy);
+    let adaptation = ChromaticAdaptation::new(&white, &SRGB_WHITE, method.unwrap_or_default());
+    let (u_prime_white, v_prime_white) = calc_uv_prime(&white);
+
+    move |pixel| {
+        let l = pixel[0] as f32 / 2.55;
+        let u = pixel[1] as f32 / 0.8557047 - 103.0;
+        let v = pixel[2] as f32 / 0.90425533 - 154.0;
+
+        let xyz = adaptation.apply(&luv_to_xyz_precomputed_white(&CIELuvColor(l, u, v), white_yy, u_prime_white, v_prime_white));
+        if xyz.is_in_srgb_gamut(tolerance) { 0 } else { 255 }
+    }
+}
+
+pub fn gamut_mask_luv(data: &[u8], mask: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32) {
+    let check = luv_gamut_check(white_x, white_y, white_yy, method, tolerance);
+    for (pixel, value) in data.chunks_exact(4).zip(mask.iter_mut()) {
+        *value = check(pixel);
+    }
+}
+
+pub fn gamut_mask_luv_alpha(data: &mut [u8], white_x: f32, white_y: f32, white_yy: f32, method: Option<ChromaticAdaptationMethod>, tolerance: f32) {
+    let check = luv_gamut_check(white_x, white_y, white_yy, method, tolerance);
+    for pixel in data.chunks_exact_mut(4) {
+        pixel[3] = check(pixel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const D65_2: (f32, f32, f32) = (0.31271, 0.32902, 1.0);
+
+    const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+
+    #[test]
+    fn test_convert_memory_srgb_to_lch() {
+        let original = srgb_test_pixels();
+        let mut data = original.clone();
+        let (x, y, yy) = D65_2;
+
+        convert_srgb_to_lch(&mut data, x, y, yy, None);
+
+        for (converted, pixel) in data.chunks(4).zip(original.chunks(4)) {
+            let srgb = SRGBColor(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0);
+            let CIELabColor(l, a, b) = xyz_to_lab(&srgb_to_xyz(&srgb), &D65);
+            let lch = CIELChColor(converted[0] as f32 / 2.55, converted[1] as f32 / 1.9029851, converted[2] as f32 / 0.7111111);
+            let CIELabColor(l_decoded, a_decoded, b_decoded) = lch_to_lab(&lch);
+
+            assert!((l - l_decoded).abs() < 0.5);
+            assert!((a - a_decoded).abs() < 2.0);
+            assert!((b - b_decoded).abs() < 2.0);
+            assert_eq!(converted[3], pixel[3]);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_lch_hue_wraparound() {
+        // Both colors have a hue slightly below 360 degrees.
+        let mut data = vec![110, 12, 57, 255, 115, 12, 60, 255];
+        let (x, y, yy) = D65_2;
+
+        convert_srgb_to_lch(&mut data, x, y, yy, None);
+        assert_eq!(data[2], 0);
+        assert_eq!(data[6], 0);
+
+        convert_lch_to_srgb(&mut data, x, y, yy, None, None);
+        assert!((data[0] as i32 - 110).abs() <= 2 && (data[1] as i32 - 12).abs() <= 2 && (data[2] as i32 - 57).abs() <= 2);
+        assert!((data[4] as i32 - 115).abs() <= 2 && (data[5] as i32 - 12).abs() <= 2 && (data[6] as i32 - 60).abs() <= 2);
+    }
+
+    #[test]
+    fn test_convert_memory_srgb_to_lchuv() {
+        let original = srgb_test_pixels();
+        let mut data = original.clone();
+        let (x, y, yy) = D65_2;
+
+        convert_srgb_to_lchuv(&mut data, x, y, yy, None);
+
+        for (converted, pixel) in data.chunks(4).zip(original.chunks(4)) {
+            let srgb = SRGBColor(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0);
+            let CIELuvColor(l, u, v) = xyz_to_luv(&srgb_to_xyz(&srgb), &D65);
+            let lchuv = CIELChuvColor(converted[0] as f32 / 2.55, converted[1] as f32 / 1.4166666, converted[2] as f32 / 0.7111111);
+            let CIELuvColor(l_decoded, u_decoded, v_decoded) = lchuv_to_luv(&lchuv);
+
+            assert!((l - l_decoded).abs() < 0.5);
+            assert!((u - u_decoded).abs() < 2.5);
+            assert!((v - v_decoded).abs() < 2.5);
+            assert_eq!(converted[3], pixel[3]);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_lchuv_round_trip() {
+        let mut data = vec![0, 0, 0, 255, 255, 255, 255, 255, 128, 128, 128, 255, 200, 100, 50, 255];
+        let original = data.clone();
+        let (x, y, yy) = D65_2;
+
+        convert_srgb_to_lchuv(&mut data, x, y, yy, None);
+        convert_lchuv_to_srgb(&mut data, x, y, yy, None, None);
+
+        for (converted, expected) in data.iter().zip(original.iter()) {
+            assert!((*converted as i32 - *expected as i32).abs() <= 2);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_srgb_to_oklab() {
+        let original = srgb_test_pixels();
+        let mut data = original.clone();
+
+        convert_srgb_to_oklab(&mut data);
+
+        for (converted, pixel) in data.chunks(4).zip(original.chunks(4)) {
+            let srgb = SRGBColor(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0);
+            let OklabColor(l, a, b) = linear_rgb_to_oklab(&srgb_to_linear_rgb(&srgb));
+
+            assert!((l - converted[0] as f32 / 255.0).abs() < 0.003);
+            assert!((a - (converted[1] as f32 / 490.3846 - 0.24)).abs() < 0.003);
+            assert!((b - (converted[2] as f32 / 490.3846 - 0.32)).abs() < 0.003);
+            assert_eq!(converted[3], pixel[3]);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_oklab_round_trip() {
+        let mut data = vec![0, 0, 0, 255, 255, 255, 255, 255, 128, 128, 128, 255, 200, 100, 50, 255];
+        let original = data.clone();
+
+        convert_srgb_to_oklab(&mut data);
+        convert_oklab_to_srgb(&mut data, None);
+
+        for (converted, expected) in data.iter().zip(original.iter()) {
+            assert!((*converted as i32 - *expected as i32).abs() <= 2);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_oklch_round_trip() {
+        let mut data = vec![0, 0, 0, 255, 255, 255, 255, 255, 128, 128, 128, 255, 200, 100, 50, 255];
+        let original = data.clone();
+
+        convert_srgb_to_oklch(&mut data);
+        convert_oklch_to_srgb(&mut data, None);
+
+        for (converted, expected) in data.iter().zip(original.iter()) {
+            assert!((*converted as i32 - *expected as i32).abs() <= 2);
+        }
+    }
+
+    #[test]
+    fn test_convert_memory_srgb_to_lab_adapts_white() {
+        // Under D50, sRGB white has to end up neutral instead of bluish.
+        let mut data = vec![255, 255, 255, 255];
+        convert_srgb_to_lab(&mut data, 0.34567, 0.35850, 1.0, None);
+        assert_eq!(data, vec![255, 121, 134, 255]);
+
+        convert_lab_to_srgb(&mut data, 0.34567, 0.35850, 1.0, Some(ChromaticAdaptationMethod::CAT16), None);
+        assert_eq!(data, vec![255, 255, 255, 255]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_compare_memory_srgb_delta_e() {
+        let (x, y, yy) = D65_2;
+        let mut data = vec![0_u8; 20 * 4 * 3];
+        for i in 0..20 {
+            data[i * 4..i * 4 + 4].copy_from_slice(&[128, 128, 128, 255]);
+            data[80 + i * 4..80 + i * 4 + 4].copy_from_slice(&[128, 128, 128, 255]);
+        }
+        // One pixel differs by a lot, one by a little.
+        data[80..84].copy_from_slice(&[255, 0, 0, 255]);
+        data[84..88].copy_from_slice(&[130, 128, 128, 255]);
+        let mut buffer = ColorBuffer::new(data.len()).unwrap();
+        buffer.as_mut_slice().copy_from_slice(&data);
+
+        let stats = compare_memory_srgb_delta_e(&mut buffer, 0, 80, 160, 80, x, y, yy, None, DifferenceMapMode::Grayscale, 100.0).unwrap();
+        let data = buffer.as_slice();
+
+        let lab_gray = xyz_to_lab(&srgb_to_xyz(&SRGBColor(128.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0)), &D65);
+        let lab_red = xyz_to_lab(&srgb_to_xyz(&SRGBColor(1.0, 0.0, 0.0)), &D65);
+        let lab_slightly_red = xyz_to_lab(&srgb_to_xyz(&SRGBColor(130.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0)), &D65);
+        let large = delta_e_2000(&lab_gray, &lab_red);
+        let small = delta_e_2000(&lab_gray, &lab_slightly_red);
+
+        assert!((stats.max - large).abs() < 0.05);
+        assert!((stats.mean - (large + small) / 20.0).abs() < 0.05);
+        assert!((stats.percentile_95 - small).abs() < 0.05);
+
+        assert_eq!(data[160 + 3], 255);
+        assert!((data[160] as f32 - large * 2.55).abs() <= 1.0);
+        assert_eq!(&data[168..172], &[0, 0, 0, 255]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_compare_memory_srgb_delta_e_heatmap() {
+        let (x, y, yy) = D65_2;
+        let data = [0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 255, 0, 0, 0, 255];
+        let mut out = [0; 8];
+
+        let stats = compare_srgb_delta_e(&data[0..8], &data[8..16], &mut out, x, y, yy, None, DifferenceMapMode::Heatmap, 50.0).unwrap();
+
+        assert!((stats.max - 100.0).abs() < 0.5);
+        assert_eq!(out, [255, 0, 0, 255, 0, 0, 0, 255]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_compare_srgb_delta_e_adaptation() {
+        // Under D50, the differences depend on how the colors were adapted to the white point.
+        let (x, y, yy) = (0.34567, 0.35850, 1.0);
+        let white = CIEXYZColor(x / y * yy, yy, (1.0 - x - y) / y * yy);
+        let data = [200, 40, 40, 255, 40, 40, 200, 255];
+        let mut out = [0; 4];
+
+        let mut results = Vec::new();
+        for method in [ChromaticAdaptationMethod::VonKries, ChromaticAdaptationMethod::CAT16] {
+            let adaptation = ChromaticAdaptation::new(&SRGB_WHITE, &white, method);
+            let lab = |r: f32, g: f32, b: f32| xyz_to_lab(&adaptation.apply(&srgb_to_xyz(&SRGBColor(r / 255.0, g / 255.0, b / 255.0))), &white);
+            let expected = delta_e_2000(&lab(200.0, 40.0, 40.0), &lab(40.0, 40.0, 200.0));
+
+            let stats = compare_srgb_delta_e(&data[0..4], &data[4..8], &mut out, x, y, yy, Some(method), DifferenceMapMode::Grayscale, 100.0).unwrap();
+            assert!((stats.max - expected).abs() < 1.0e-3);
+            results.push(stats.max);
+        }
+        assert!((results[0] - results[1]).abs() > 0.1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_compare_srgb_delta_e_errors() {
+        let (x, y, yy) = D65_2;
+        let data = [128; 16];
+        let mut out = [0; 8];
+        let mode = DifferenceMapMode::Grayscale;
+
+        for max_delta_e in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let result = compare_srgb_delta_e(&data[0..8], &data[8..16], &mut out, x, y, yy, None, mode, max_delta_e);
+            assert!(matches!(result, Err(ColorSpacesError::InvalidMaxDeltaE { .. })), "{}", max_delta_e);
+        }
+
+        let result = compare_srgb_delta_e(&data[0..8], &data[8..12], &mut out, x, y, yy, None, mode, 10.0);
+        assert!(matches!(result, Err(ColorSpacesError::LengthMismatch { len: 8, other_len: 4 })));
+        assert!(compare_srgb_delta_e(&data[0..12], &data[4..16], &mut out, x, y, yy, None, mode, 10.0).is_err());
+        assert_eq!(out, [0; 8]);
+
+        let mut buffer = ColorBuffer::new(24).unwrap();
+        assert!(compare_memory_srgb_delta_e(&mut buffer, 0, 8, 16, 8, x, y, yy, None, mode, 0.0).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_compare_memory_srgb_delta_e_regions() {
+        let (x, y, yy) = D65_2;
+        let mut buffer = ColorBuffer::new(24).unwrap();
+        let mut compare = |offset_a, offset_b, offset_out, len| {
+            compare_memory_srgb_delta_e(&mut buffer, offset_a, offset_b, offset_out, len, x, y, yy, None, DifferenceMapMode::Grayscale, 100.0)
+        };
+
+        assert!(compare(0, 8, 16, 8).is_ok());
+        // The inputs may be the same region, but the output must not overlap them.
+        assert!(compare(0, 0, 16, 8).is_ok());
+        assert!(compare(0, 8, 12, 8).is_err());
+        assert!(compare(0, 8, 20, 8).is_err());
+        assert!(compare(0, 2, 16, 8).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_convert_memory_invalid_white_point() {
+        let mut buffer = ColorBuffer::new(8).unwrap();
+        buffer.as_mut_slice().fill(128);
+
+        let result = convert_memory_srgb_to_lab(&mut buffer, 0, 8, 0.3127, 0.0, 1.0, None);
+        assert_eq!(result, Err(ColorSpacesError::InvalidWhitePoint { x: 0.3127, y: 0.0, yy: 1.0 }));
+        assert!(buffer.as_slice().iter().all(|&v| v == 128));
+
+        assert!(gamut_mask_memory_luv(&mut buffer, 0, 8, 0.3127, 0.3290, -1.0, None, 0.01, None).is_err());
+        assert!(compare_memory_srgb_delta_e(&mut buffer, 0, 0, 4, 4, 0.8, 0.3, 1.0, None, DifferenceMapMode::Grayscale, 10.0).is_err());
+    }
+
+    #[test]
+    fn test_convert_memory_lab_to_srgb_gamut_mapping() {
+        let (x, y, yy) = D65_2;
+        let encoded = [127, 235, 29, 255];
+        let CIELChColor(_, _, h_original) = lab_to_lch(&CIELabColor(127.0 / 2.55, 235.0 / 1.1383928 - 106.0, 29.0 / 1.0493827 - 128.0));
+
+        let hue_error = |method: GamutMappingMethod| {
+            let mut data = encoded.to_vec();
+            convert_lab_to_srgb(&mut data, x, y, yy, None, Some(method));
+            let srgb = SRGBColor(data[0] as f32 / 255.0, data[1] as f32 / 255.0, data[2] as f32 / 255.0);
+            (xyz_to_lch(&srgb_to_xyz(&srgb), &D65).2 - h_original).abs()
+        };
+
+        assert!(hue_error(GamutMappingMethod::MinDE) < 2.0);
+        assert!(hue_error(GamutMappingMethod::MinDE) < hue_error(GamutMappingMethod::Clip));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_gamut_mask_memory_lab() {
+        let (x, y, yy) = D65_2;
+        // sRGB gray, followed by the out-of-gamut CIELab color (50, 100, -100)
+        let mut buffer = ColorBuffer::new(12).unwrap();
+        buffer.as_mut_slice().copy_from_slice(&[128, 128, 128, 255, 127, 235, 29, 255, 1, 1, 1, 1]);
+        convert_memory_srgb_to_lab(&mut buffer, 0, 4, x, y, yy, None).unwrap();
+
+        gamut_mask_memory_lab(&mut buffer, 0, 8, x, y, yy, None, 0.01, Some(8)).unwrap();
+        assert_eq!(&buffer.as_slice()[8..12], &[0, 255, 1, 1]);
+        assert_eq!(buffer.as_slice()[3], 255);
+
+        // The mask must not overlap the pixels.
+        assert!(gamut_mask_memory_lab(&mut buffer, 0, 8, x, y, yy, None, 0.01, Some(6)).is_err());
+        assert!(gamut_mask_memory_lab(&mut buffer, 0, 8, x, y, yy, None, 0.01, Some(11)).is_err());
+
+        gamut_mask_memory_lab(&mut buffer, 0, 8, x, y, yy, None, 0.01, None).unwrap();
+        assert_eq!(buffer.as_slice()[3], 0);
+        assert_eq!(buffer.as_slice()[7], 255);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_gamut_mask_memory_luv() {
+        let (x, y, yy) = D65_2;
+        let mut data = vec![0, 200, 50, 255, 255, 255, 255, 255];
+        convert_srgb_to_luv(&mut data, x, y, yy, None);
+        // Push the chroma of the second pixel far out of sRGB
+        data[5] = 0;
+        data[6] = 255;
 
-        data[i * 4 + 0] = (f32::to_bits((r * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 1] = (f32::to_bits((g * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
-        data[i * 4 + 2] = (f32::to_bits((b * 255.0).clamp(0.0, 255.0) + 256.5) >> 15) as u8;
+        gamut_mask_luv_alpha(&mut data, x, y, yy, None, 0.01);
+        assert_eq!(data[3], 0);
+        assert_eq!(data[7], 255);
     }
 }
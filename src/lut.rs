@@ -0,0 +1,154 @@
+use crate::LinearRGBColor;
+use crate::transfer::{TransferFunctions, DefaultTransfer};
+#[cfg(not(feature = "accurate"))]
+use crate::rgb::srgb_digamma_approx;
+
+#[cfg(feature = "gamma-lut")]
+use std::sync::OnceLock;
+
+/*
+Lookup tables for the 8-bit sRGB kernels.
+
+An 8-bit sRGB channel only has 256 possible values, so instead of calling srgb_digamma()
+for every pixel, the linear values are looked up in a table that is computed at compile time.
+
+The entries are the same as the results of DefaultTransfer, so using the table does not change any results.
+*/
+static SRGB_LINEARIZATION_TABLE: [f32; 256] = build_srgb_linearization_table();
+
+const fn build_srgb_linearization_table() -> [f32; 256] {
+    let mut table = [0.0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = srgb_linearization_entry(i as f32 / 255.0);
+        i += 1;
+    }
+    table
+}
+
+#[cfg(not(feature = "accurate"))]
+const fn srgb_linearization_entry(val: f32) -> f32 {
+    srgb_digamma_approx(val)
+}
+
+/*
+powf() can not be called at compile time, so x^2.4 is computed as x^2 * (x^2)^(1/5),
+with the fifth root found by Newton's method. This matches srgb_digamma_f64() to a few ulp
+of an f64, far below the precision of the rounded f32 result.
+*/
+#[cfg(feature = "accurate")]
+const fn srgb_linearization_entry(val: f32) -> f32 {
+    let val = val as f64;
+    if val <= 0.04045 {
+        return (val / 12.92) as f32;
+    }
+
+    let x = (val + 0.055) / 1.055;
+    let x_squared = x * x;
+    let mut fifth_root = 1.0;
+    loop {
+        let fifth_root_4 = fifth_root * fifth_root * fifth_root * fifth_root;
+        let next = fifth_root - (fifth_root_4 * fifth_root - x_squared) / (5.0 * fifth_root_4);
+        if next >= fifth_root {
+            break;
+        }
+        fifth_root = next;
+    }
+    (x_squared * fifth_root) as f32
+}
+
+pub(crate) fn srgb_linearization_table() -> &'static [f32; 256] {
+    &SRGB_LINEARIZATION_TABLE
+}
+
+// See convert_srgb_to_xyz() for how this works.
+#[inline(always)]
+fn encode_u8(value: f32) -> u8 {
+    (f32::to_bits(value.clamp(0.0, 255.0) + 256.5) >> 15) as u8
+}
+
+/*
+Size of the optional output table. Linear values are rounded to the nearest of these steps
+before the lookup. The steepest part of the sRGB curve is the linear segment near black with a
+slope of 12.92, so one step is worth about 0.8 of an output byte and the table can be off by one.
+*/
+#[cfg(feature = "gamma-lut")]
+pub(crate) const GAMMA_TABLE_SIZE: usize = 4096;
+
+#[cfg(feature = "gamma-lut")]
+fn build_srgb_gamma_table() -> [u8; GAMMA_TABLE_SIZE] {
+    let mut table = [0; GAMMA_TABLE_SIZE];
+    for (i, entry) in table.iter_mut().enumerate() {
+        *entry = encode_u8(DefaultTransfer::srgb_gamma(i as f32 / (GAMMA_TABLE_SIZE - 1) as f32) * 255.0);
+    }
+    table
+}
+
+/*
+Encodes linear RGB values into 8-bit sRGB. With the "gamma-lut" feature, the gamma is taken
+from a lazily built table, otherwise it is computed for every value.
+*/
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct SRGBEncoder {
+    #[cfg(feature = "gamma-lut")]
+    table: &'static [u8; GAMMA_TABLE_SIZE]
+}
+
+impl SRGBEncoder {
+
+    #[cfg(feature = "gamma-lut")]
+    pub(crate) fn new() -> SRGBEncoder {
+        static TABLE: OnceLock<[u8; GAMMA_TABLE_SIZE]> = OnceLock::new();
+        SRGBEncoder { table: TABLE.get_or_init(build_srgb_gamma_table) }
+    }
+
+    #[cfg(not(feature = "gamma-lut"))]
+    pub(crate) fn new() -> SRGBEncoder {
+        SRGBEncoder {}
+    }
+
+    #[cfg(feature = "gamma-lut")]
+    #[inline(always)]
+    fn encode_channel(&self, val: f32) -> u8 {
+        self.table[(val.clamp(0.0, 1.0) * (GAMMA_TABLE_SIZE - 1) as f32 + 0.5) as usize]
+    }
+
+    #[cfg(not(feature = "gamma-lut"))]
+    #[inline(always)]
+    fn encode_channel(&self, val: f32) -> u8 {
+        encode_u8(DefaultTransfer::srgb_gamma(val) * 255.0)
+    }
+
+    #[inline(always)]
+    pub(crate) fn encode(&self, lin_rgb: &LinearRGBColor) -> (u8, u8, u8) {
+        let &LinearRGBColor(r_lin, g_lin, b_lin) = lin_rgb;
+        (self.encode_channel(r_lin), self.encode_channel(g_lin), self.encode_channel(b_lin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::srgb_gamma_f64;
+
+    #[test]
+    fn test_srgb_linearization_table() {
+        let table = srgb_linearization_table();
+        for (v, &entry) in table.iter().enumerate() {
+            assert_eq!(entry, DefaultTransfer::srgb_digamma(v as f32 / 255.0));
+        }
+    }
+
+    #[test]
+    fn test_srgb_encoder() {
+        let encoder = SRGBEncoder::new();
+        assert_eq!(encoder.encode(&LinearRGBColor(-0.5, 0.0, 1.5)), (0, 0, 255));
+        for i in 0..=10000 {
+            let val = i as f32 / 10000.0;
+            let expected = (srgb_gamma_f64(val as f64) * 255.0).round() as u8;
+            let (r, _, _) = encoder.encode(&LinearRGBColor(val, val, val));
+            assert!(r.abs_diff(expected) <= 1, "{} {} {}", val, r, expected);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{TristimulusColor, LinearRGBColor, CIEXYZColor};
+use crate::chromatic_adaptation::{Matrix3, multiply_matrix_vector, multiply_matrices};
+use crate::color_space::ColorSpace;
+use crate::transfer::{TransferFunctions, DefaultTransfer};
+use crate::rgb::{LINEAR_RGB_TO_XYZ, XYZ_TO_LINEAR_RGB};
+use crate::cielab::CORRECTION_FACTOR_3RD_ROOT;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub(crate) const LINEAR_RGB_TO_LMS: Matrix3 = [
+    [0.41222146, 0.53633255, 0.051445995],
+    [0.2119035, 0.6806995, 0.10739696],
+    [0.08830246, 0.28171885, 0.6299787]
+];
+
+pub(crate) const LMS_TO_LINEAR_RGB: Matrix3 = [
+    [4.0767417, -3.3077116, 0.23096994],
+    [-1.268438, 2.6097574, -0.34131938],
+    [-0.0041960863, -0.7034186, 1.7076147]
+];
+
+/*
+XYZ to LMS and back, as products of the linear sRGB matrices. Oklab publishes its own XYZ matrix,
+but it does not exactly match the sRGB primaries, so converting through XYZ would give slightly
+different results than converting from linear sRGB.
+*/
+pub(crate) const XYZ_TO_LMS: Matrix3 = multiply_matrices(&LINEAR_RGB_TO_LMS, &XYZ_TO_LINEAR_RGB);
+
+pub(crate) const LMS_TO_XYZ: Matrix3 = multiply_matrices(&LINEAR_RGB_TO_XYZ, &LMS_TO_LINEAR_RGB);
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct OklabColor(pub f32, pub f32, pub f32);
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl OklabColor {
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(l: f32, a: f32, b: f32) -> OklabColor {
+        OklabColor(l, a, b)
+    }
+}
+
+impl TristimulusColor for OklabColor {
+
+    fn get_0(&self) -> f32 {
+        self.0
+    }
+
+    fn get_1(&self) -> f32 {
+        self.1
+    }
+
+    fn get_2(&self) -> f32 {
+        self.2
+    }
+}
+
+// The linear values of Oklab are its LMS cone responses.
+impl ColorSpace for OklabColor {
+
+    const WHITE_RELATIVE: bool = false;
+
+    fn from_components(l: f32, a: f32, b: f32) -> OklabColor {
+        OklabColor(l, a, b)
+    }
+
+    fn to_linear(&self, _white: &CIEXYZColor) -> CIEXYZColor {
+        let (l, m, s) = oklab_to_lms(self);
+        CIEXYZColor(l, m, s)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> OklabColor {
+        OklabColor::from_linear_with::<DefaultTransfer>(linear, white)
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, _white: &CIEXYZColor) -> OklabColor {
+        let &CIEXYZColor(l, m, s) = linear;
+        lms_to_oklab::<T>(l, m, s)
+    }
+
+    fn linear_to_xyz_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        LMS_TO_XYZ
+    }
+
+    fn xyz_to_linear_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        XYZ_TO_LMS
+    }
+}
+
+/*
+Cube root using the same approximation as lab_gamma(). Unlike CIELab, Oklab has no
+linear segment near zero, and out-of-gamut colors can produce negative LMS values,
+so the sign is split off before the approximation and restored afterwards.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_gamma(val: f32) -> f32 {
+    DefaultTransfer::oklab_gamma(val)
+}
+
+#[inline(always)]
+pub(crate) fn oklab_gamma_approx(val: f32) -> f32 {
+    if val == 0.0 {
+        return 0.0;
+    }
+
+    let float_bits: u32 = f32::to_bits(val);
+    let sign_bit: u32 = float_bits & 0x80000000;
+    let exponent: i32 = ((float_bits >> 23) & 0xff) as i32 - 126;
+    let new_float_bits: u32 = (float_bits & 0x007fffff) | 0x3f000000;
+    let x: f32 = f32::from_bits(new_float_bits);
+
+    let y = ((((x * 0.13380815 - 0.6322613) * 
+                x + 1.2763454) *
+                    x - 1.4815698) * 
+                        x + 1.3275858) * 
+                            x + 0.37609282;
+    
+    let exponent_new = exponent / 3;
+    let correction_shift = f32::from_bits(((exponent_new + 127) as u32 & 0xff) << 23);
+    let correction_index = ((exponent - exponent_new * 3) + 2) as usize;
+    let correction_factor = CORRECTION_FACTOR_3RD_ROOT[correction_index];
+    let cube_root = y * correction_shift * correction_factor;
+    f32::from_bits(f32::to_bits(cube_root) | sign_bit)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_digamma(val: f32) -> f32 {
+    val * val * val
+}
+
+#[inline(always)]
+pub(crate) fn lms_to_oklab<T: TransferFunctions>(l: f32, m: f32, s: f32) -> OklabColor {
+    let l_gamma = T::oklab_gamma(l);
+    let m_gamma = T::oklab_gamma(m);
+    let s_gamma = T::oklab_gamma(s);
+
+    OklabColor(
+        l_gamma * 0.21045426 + m_gamma * 0.7936178 + s_gamma * -0.004072047,
+        l_gamma * 1.9779985 + m_gamma * -2.4285922 + s_gamma * 0.4505937,
+        l_gamma * 0.025904037 + m_gamma * 0.78277177 + s_gamma * -0.80867577
+    )
+}
+
+#[inline(always)]
+pub(crate) fn oklab_to_lms(oklab: &OklabColor) -> (f32, f32, f32) {
+    let &OklabColor(l, a, b) = oklab;
+
+    let l_gamma = l + a * 0.39633778 + b * 0.21580376;
+    let m_gamma = l + a * -0.105561346 + b * -0.06385417;
+    let s_gamma = l + a * -0.08948418 + b * -1.2914855;
+
+    (oklab_digamma(l_gamma), oklab_digamma(m_gamma), oklab_digamma(s_gamma))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn linear_rgb_to_oklab(lin_rgb: &LinearRGBColor) -> OklabColor {
+    let &LinearRGBColor(r_lin, g_lin, b_lin) = lin_rgb;
+    let CIEXYZColor(l, m, s) = multiply_matrix_vector(&LINEAR_RGB_TO_LMS, &CIEXYZColor(r_lin, g_lin, b_lin));
+    lms_to_oklab::<DefaultTransfer>(l, m, s)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_to_linear_rgb(oklab: &OklabColor) -> LinearRGBColor {
+    let (l, m, s) = oklab_to_lms(oklab);
+    let CIEXYZColor(r_lin, g_lin, b_lin) = multiply_matrix_vector(&LMS_TO_LINEAR_RGB, &CIEXYZColor(l, m, s));
+    LinearRGBColor(r_lin, g_lin, b_lin)
+}
+
+/*
+Oklab is defined relative to D65, so the XYZ variants do not take a white point.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_oklab(xyz: &CIEXYZColor) -> OklabColor {
+    let CIEXYZColor(l, m, s) = multiply_matrix_vector(&XYZ_TO_LMS, xyz);
+    lms_to_oklab::<DefaultTransfer>(l, m, s)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_to_xyz(oklab: &OklabColor) -> CIEXYZColor {
+    let (l, m, s) = oklab_to_lms(oklab);
+    multiply_matrix_vector(&LMS_TO_XYZ, &CIEXYZColor(l, m, s))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{CompareMargin, linear_rgb_to_xyz};
+
+    const MARGIN: f32 = 1.0e-3;
+
+    #[test]
+    fn test_oklab_gamma() {
+        assert_eq!(oklab_gamma(0.0), 0.0);
+        assert!((oklab_gamma(0.125) - 0.5).abs() < 1.0e-5);
+        assert!((oklab_gamma(-0.125) + 0.5).abs() < 1.0e-5);
+        assert!((oklab_gamma(0.7) - 0.887904).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_linear_rgb_to_oklab() {
+        assert!(linear_rgb_to_oklab(&LinearRGBColor(1.0, 0.0, 0.0)).equal_within(OklabColor(0.627955, 0.224863, 0.125846), MARGIN));
+        assert!(linear_rgb_to_oklab(&LinearRGBColor(0.0, 1.0, 0.0)).equal_within(OklabColor(0.866440, -0.233888, 0.179498), MARGIN));
+        assert!(linear_rgb_to_oklab(&LinearRGBColor(0.0, 0.0, 1.0)).equal_within(OklabColor(0.452014, -0.032457, -0.311528), MARGIN));
+        assert!(linear_rgb_to_oklab(&LinearRGBColor(1.0, 1.0, 1.0)).equal_within(OklabColor(1.0, 0.0, 0.0), MARGIN));
+        assert!(linear_rgb_to_oklab(&LinearRGBColor(0.0, 0.0, 0.0)).equal_within(OklabColor(0.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_oklab_to_linear_rgb() {
+        assert!(oklab_to_linear_rgb(&OklabColor(0.627955, 0.224863, 0.125846)).equal_within(LinearRGBColor(1.0, 0.0, 0.0), MARGIN));
+        assert!(oklab_to_linear_rgb(&OklabColor(0.866440, -0.233888, 0.179498)).equal_within(LinearRGBColor(0.0, 1.0, 0.0), MARGIN));
+        assert!(oklab_to_linear_rgb(&OklabColor(0.452014, -0.032457, -0.311528)).equal_within(LinearRGBColor(0.0, 0.0, 1.0), MARGIN));
+        assert!(oklab_to_linear_rgb(&OklabColor(1.0, 0.0, 0.0)).equal_within(LinearRGBColor(1.0, 1.0, 1.0), MARGIN));
+    }
+
+    #[test]
+    fn test_xyz_to_oklab() {
+        assert!(xyz_to_oklab(&CIEXYZColor(0.950, 1.0, 1.089)).equal_within(OklabColor(1.0, 0.0, 0.0), MARGIN));
+        assert!(xyz_to_oklab(&CIEXYZColor(1.0, 0.0, 0.0)).equal_within(OklabColor(0.450, 1.236, -0.019), MARGIN));
+        assert!(xyz_to_oklab(&CIEXYZColor(0.0, 1.0, 0.0)).equal_within(OklabColor(0.922, -0.671, 0.263), MARGIN));
+        assert!(xyz_to_oklab(&CIEXYZColor(0.0, 0.0, 1.0)).equal_within(OklabColor(0.153, -1.415, -0.449), MARGIN));
+    }
+
+    #[test]
+    fn test_oklab_to_xyz() {
+        assert!(oklab_to_xyz(&OklabColor(1.0, 0.0, 0.0)).equal_within(CIEXYZColor(0.9505, 1.0, 1.089), MARGIN));
+        assert!(oklab_to_xyz(&OklabColor(0.4500, 1.2357, -0.0193)).equal_within(CIEXYZColor(1.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_xyz_matches_linear_rgb() {
+        for &lin_rgb in &[LinearRGBColor(1.0, 0.0, 0.0), LinearRGBColor(0.2, 0.7, 0.4), LinearRGBColor(0.05, 0.1, 0.9)] {
+            let xyz = linear_rgb_to_xyz(&lin_rgb);
+            assert!(xyz_to_oklab(&xyz).equal_within(linear_rgb_to_oklab(&lin_rgb), 1.0e-5));
+            assert!(oklab_to_xyz(&linear_rgb_to_oklab(&lin_rgb)).equal_within(xyz, 1.0e-5));
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{TristimulusColor, CIEXYZColor, OklabColor, xyz_to_oklab, oklab_to_xyz};
+use crate::cielch::{cartesian_to_polar, polar_to_cartesian};
+use crate::color_space::ColorSpace;
+use crate::transfer::TransferFunctions;
+use crate::chromatic_adaptation::Matrix3;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct OklchColor(pub f32, pub f32, pub f32);
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl OklchColor {
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(l: f32, c: f32, h: f32) -> OklchColor {
+        OklchColor(l, c, h)
+    }
+}
+
+impl TristimulusColor for OklchColor {
+
+    fn get_0(&self) -> f32 {
+        self.0
+    }
+
+    fn get_1(&self) -> f32 {
+        self.1
+    }
+
+    fn get_2(&self) -> f32 {
+        self.2
+    }
+}
+
+impl ColorSpace for OklchColor {
+
+    const WHITE_RELATIVE: bool = false;
+
+    fn from_components(l: f32, c: f32, h: f32) -> OklchColor {
+        OklchColor(l, c, h)
+    }
+
+    fn to_linear(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        oklch_to_oklab(self).to_linear(white)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> OklchColor {
+        oklab_to_oklch(&OklabColor::from_linear(linear, white))
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, white: &CIEXYZColor) -> OklchColor {
+        oklab_to_oklch(&OklabColor::from_linear_with::<T>(linear, white))
+    }
+
+    fn linear_to_xyz_matrix(white: &CIEXYZColor) -> Matrix3 {
+        OklabColor::linear_to_xyz_matrix(white)
+    }
+
+    fn xyz_to_linear_matrix(white: &CIEXYZColor) -> Matrix3 {
+        OklabColor::xyz_to_linear_matrix(white)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklab_to_oklch(oklab: &OklabColor) -> OklchColor {
+    let &OklabColor(l, a, b) = oklab;
+    let (c, h) = cartesian_to_polar(a, b);
+    OklchColor(l, c, h)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklch_to_oklab(oklch: &OklchColor) -> OklabColor {
+    let &OklchColor(l, c, h) = oklch;
+    let (a, b) = polar_to_cartesian(c, h);
+    OklabColor(l, a, b)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn xyz_to_oklch(xyz: &CIEXYZColor) -> OklchColor {
+    oklab_to_oklch(&xyz_to_oklab(xyz))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn oklch_to_xyz(oklch: &OklchColor) -> CIEXYZColor {
+    oklab_to_xyz(&oklch_to_oklab(oklch))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::CompareMargin;
+
+    const MARGIN: f32 = 1.0e-3;
+
+    #[test]
+    fn test_oklab_to_oklch() {
+        assert!(oklab_to_oklch(&OklabColor(0.627955, 0.224863, 0.125846)).equal_within(OklchColor(0.627955, 0.257683, 29.234), MARGIN));
+        assert!(oklab_to_oklch(&OklabColor(0.452014, -0.032457, -0.311528)).equal_within(OklchColor(0.452014, 0.313214, 264.052), MARGIN));
+        assert!(oklab_to_oklch(&OklabColor(1.0, 0.0, 0.0)).equal_within(OklchColor(1.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_oklch_to_oklab() {
+        assert!(oklch_to_oklab(&OklchColor(0.627955, 0.257683, 29.234)).equal_within(OklabColor(0.627955, 0.224863, 0.125846), MARGIN));
+        assert!(oklch_to_oklab(&OklchColor(0.452014, 0.313214, 264.052)).equal_within(OklabColor(0.452014, -0.032457, -0.311528), MARGIN));
+        assert!(oklch_to_oklab(&OklchColor(1.0, 0.0, 90.0)).equal_within(OklabColor(1.0, 0.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_xyz_to_oklch() {
+        assert!(xyz_to_oklch(&CIEXYZColor(0.4123908, 0.212639, 0.0193308)).equal_within(OklchColor(0.627955, 0.257683, 29.234), MARGIN));
+    }
+
+    #[test]
+    fn test_oklch_to_xyz() {
+        assert!(oklch_to_xyz(&OklchColor(0.627955, 0.257683, 29.234)).equal_within(CIEXYZColor(0.4123908, 0.212639, 0.0193308), MARGIN));
+    }
+}
@@ -0,0 +1,602 @@
+use crate::*;
+use crate::chromatic_adaptation::{Matrix3, multiply_matrix_vector};
+use crate::error::check_white_point;
+#[cfg(feature = "parallel")]
+use crate::thread_pool::{convert_parallel, convert_rect_in_parallel};
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use core::marker::PhantomData;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorSpaceId {
+    SRGB = 0,
+    LinearRGB = 1,
+    CIEXYZ = 2,
+    CIELab = 3,
+    CIELCh = 4,
+    CIELuv = 5,
+    CIELChuv = 6,
+    Oklab = 7,
+    Oklch = 8
+}
+
+/*
+Parses the names of the color space constants of p5.colorspaces, e.g. "sRGB" or "CIELab".
+*/
+impl core::str::FromStr for ColorSpaceId {
+    type Err = ColorSpacesError;
+
+    fn from_str(name: &str) -> Result<ColorSpaceId, ColorSpacesError> {
+        match name {
+            "sRGB" => Ok(ColorSpaceId::SRGB),
+            "linear RGB" => Ok(ColorSpaceId::LinearRGB),
+            "CIEXYZ" => Ok(ColorSpaceId::CIEXYZ),
+            "CIELab" => Ok(ColorSpaceId::CIELab),
+            "CIELCh" => Ok(ColorSpaceId::CIELCh),
+            "CIELuv" => Ok(ColorSpaceId::CIELuv),
+            "CIELChuv" => Ok(ColorSpaceId::CIELChuv),
+            "Oklab" => Ok(ColorSpaceId::Oklab),
+            "Oklch" => Ok(ColorSpaceId::Oklch),
+            _ => Err(ColorSpacesError::UnsupportedSpace)
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn parse_color_space(name: &str) -> Result<ColorSpaceId, ColorSpacesError> {
+    name.parse()
+}
+
+/*
+Byte encodings of the color spaces inside of RGBA u8 buffers. These are the same
+encodings the convert_memory_* kernels use.
+*/
+pub(crate) trait U8Encoding: ColorSpace {
+
+    fn decode_u8(c0: u8, c1: u8, c2: u8) -> Self;
+
+    fn encode_u8(&self) -> (u8, u8, u8);
+}
+
+// See convert_srgb_to_xyz() for how this works.
+#[inline(always)]
+fn encode_u8(value: f32) -> u8 {
+    (f32::to_bits(value.clamp(0.0, 255.0) + 256.5) >> 15) as u8
+}
+
+// See convert_srgb_to_lch() for how the hue wraps around.
+#[inline(always)]
+fn encode_hue_u8(value: f32) -> u8 {
+    (f32::to_bits(value + 256.5) >> 15) as u8
+}
+
+impl U8Encoding for SRGBColor {
+
+    fn decode_u8(r: u8, g: u8, b: u8) -> SRGBColor {
+        SRGBColor(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &SRGBColor(r, g, b) = self;
+        (encode_u8(r * 255.0), encode_u8(g * 255.0), encode_u8(b * 255.0))
+    }
+}
+
+impl U8Encoding for LinearRGBColor {
+
+    fn decode_u8(r: u8, g: u8, b: u8) -> LinearRGBColor {
+        LinearRGBColor(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &LinearRGBColor(r, g, b) = self;
+        (encode_u8(r * 255.0), encode_u8(g * 255.0), encode_u8(b * 255.0))
+    }
+}
+
+impl U8Encoding for CIEXYZColor {
+
+    fn decode_u8(x: u8, y: u8, z: u8) -> CIEXYZColor {
+        CIEXYZColor(x as f32 / 269.0, y as f32 / 255.0, z as f32 / 235.0)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &CIEXYZColor(x, y, z) = self;
+        (encode_u8(x * 269.0), encode_u8(y * 255.0), encode_u8(z * 235.0))
+    }
+}
+
+impl U8Encoding for CIELabColor {
+
+    fn decode_u8(l: u8, a: u8, b: u8) -> CIELabColor {
+        CIELabColor(l as f32 / 2.55, a as f32 / 1.1383928 - 106.0, b as f32 / 1.0493827 - 128.0)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &CIELabColor(l, a, b) = self;
+        (encode_u8(l * 2.55), encode_u8((a + 106.0) * 1.1383928), encode_u8((b + 128.0) * 1.0493827))
+    }
+}
+
+impl U8Encoding for CIELChColor {
+
+    fn decode_u8(l: u8, c: u8, h: u8) -> CIELChColor {
+        CIELChColor(l as f32 / 2.55, c as f32 / 1.9029851, h as f32 / 0.7111111)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &CIELChColor(l, c, h) = self;
+        (encode_u8(l * 2.55), encode_u8(c * 1.9029851), encode_hue_u8(h * 0.7111111))
+    }
+}
+
+impl U8Encoding for CIELuvColor {
+
+    fn decode_u8(l: u8, u: u8, v: u8) -> CIELuvColor {
+        CIELuvColor(l as f32 / 2.55, u as f32 / 0.8557047 - 103.0, v as f32 / 0.90425533 - 154.0)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &CIELuvColor(l, u, v) = self;
+        (encode_u8(l * 2.55), encode_u8((u + 103.0) * 0.8557047), encode_u8((v + 154.0) * 0.90425533))
+    }
+}
+
+impl U8Encoding for CIELChuvColor {
+
+    fn decode_u8(l: u8, c: u8, h: u8) -> CIELChuvColor {
+        CIELChuvColor(l as f32 / 2.55, c as f32 / 1.4166666, h as f32 / 0.7111111)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &CIELChuvColor(l, c, h) = self;
+        (encode_u8(l * 2.55), encode_u8(c * 1.4166666), encode_hue_u8(h * 0.7111111))
+    }
+}
+
+impl U8Encoding for OklabColor {
+
+    fn decode_u8(l: u8, a: u8, b: u8) -> OklabColor {
+        OklabColor(l as f32 / 255.0, a as f32 / 490.3846 - 0.24, b as f32 / 490.3846 - 0.32)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &OklabColor(l, a, b) = self;
+        (encode_u8(l * 255.0), encode_u8((a + 0.24) * 490.3846), encode_u8((b + 0.32) * 490.3846))
+    }
+}
+
+impl U8Encoding for OklchColor {
+
+    fn decode_u8(l: u8, c: u8, h: u8) -> OklchColor {
+        OklchColor(l as f32 / 255.0, c as f32 / 772.7273, h as f32 / 0.7111111)
+    }
+
+    fn encode_u8(&self) -> (u8, u8, u8) {
+        let &OklchColor(l, c, h) = self;
+        (encode_u8(l * 255.0), encode_u8(c * 772.7273), encode_hue_u8(h * 0.7111111))
+    }
+}
+
+/*
+Calls a generic function with the color space types matching a pair of ColorSpaceIds.
+*/
+macro_rules! dispatch_color_spaces {
+    ($source:expr, $target:expr, $func:ident($($arg:expr),*)) => {
+        match $source {
+            ColorSpaceId::SRGB => dispatch_color_spaces!(@target SRGBColor, $target, $func($($arg),*)),
+            ColorSpaceId::LinearRGB => dispatch_color_spaces!(@target LinearRGBColor, $target, $func($($arg),*)),
+            ColorSpaceId::CIEXYZ => dispatch_color_spaces!(@target CIEXYZColor, $target, $func($($arg),*)),
+            ColorSpaceId::CIELab => dispatch_color_spaces!(@target CIELabColor, $target, $func($($arg),*)),
+            ColorSpaceId::CIELCh => dispatch_color_spaces!(@target CIELChColor, $target, $func($($arg),*)),
+            ColorSpaceId::CIELuv => dispatch_color_spaces!(@target CIELuvColor, $target, $func($($arg),*)),
+            ColorSpaceId::CIELChuv => dispatch_color_spaces!(@target CIELChuvColor, $target, $func($($arg),*)),
+            ColorSpaceId::Oklab => dispatch_color_spaces!(@target OklabColor, $target, $func($($arg),*)),
+            ColorSpaceId::Oklch => dispatch_color_spaces!(@target OklchColor, $target, $func($($arg),*))
+        }
+    };
+    (@target $from:ty, $target:expr, $func:ident($($arg:expr),*)) => {
+        match $target {
+            ColorSpaceId::SRGB => $func::<$from, SRGBColor>($($arg),*),
+            ColorSpaceId::LinearRGB => $func::<$from, LinearRGBColor>($($arg),*),
+            ColorSpaceId::CIEXYZ => $func::<$from, CIEXYZColor>($($arg),*),
+            ColorSpaceId::CIELab => $func::<$from, CIELabColor>($($arg),*),
+            ColorSpaceId::CIELCh => $func::<$from, CIELChColor>($($arg),*),
+            ColorSpaceId::CIELuv => $func::<$from, CIELuvColor>($($arg),*),
+            ColorSpaceId::CIELChuv => $func::<$from, CIELChuvColor>($($arg),*),
+            ColorSpaceId::Oklab => $func::<$from, OklabColor>($($arg),*),
+            ColorSpaceId::Oklch => $func::<$from, OklchColor>($($arg),*)
+        }
+    };
+}
+
+/*
+Conversion between any two color spaces, set up once and then applied to as many
+buffers as needed. All matrices and the chromatic adaptation between the white points
+are fused into a single matrix, so there is no intermediate sRGB or CIEXYZ step.
+
+Conversions into sRGB and linear RGB apply the selected gamut mapping. All other
+color spaces are clamped to the range of their byte encoding.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Copy, Clone)]
+pub struct ConversionPipeline {
+    source_space: ColorSpaceId,
+    target_space: ColorSpaceId,
+    source_white: CIEXYZColor,
+    target_white: CIEXYZColor,
+    matrix: Matrix3,
+    gamut_mapping: Option<GamutMappingMethod>,
+    accurate: bool
+}
+
+fn fused_conversion<From: ColorSpace, To: ColorSpace>(source_white: &CIEXYZColor, target_white: &CIEXYZColor, method: ChromaticAdaptationMethod) -> (CIEXYZColor, CIEXYZColor, Matrix3) {
+    let converter = Converter::<From, To>::new(source_white, target_white, method);
+    (converter.source_white, converter.target_white, converter.matrix)
+}
+
+fn convert_pixels_u8<From: U8Encoding, To: U8Encoding>(pipeline: &ConversionPipeline, data: &mut [u8]) {
+    if pipeline.accurate {
+        convert_pixels_u8_with::<From, To, Exact>(pipeline, data);
+    } else {
+        convert_pixels_u8_with::<From, To, DefaultTransfer>(pipeline, data);
+    }
+}
+
+fn convert_pixels_u8_with<From: U8Encoding, To: U8Encoding, T: TransferFunctions>(pipeline: &ConversionPipeline, data: &mut [u8]) {
+    let converter = Converter::<From, To> {
+        source_white: pipeline.source_white,
+        target_white: pipeline.target_white,
+        matrix: pipeline.matrix,
+        spaces: PhantomData
+    };
+
+    for pixel in data.chunks_exact_mut(4) {
+        let color = From::decode_u8(pixel[0], pixel[1], pixel[2]);
+
+        let (c0, c1, c2) = match pipeline.gamut_mapping {
+            Some(method) => {
+                // The linear values of both RGB spaces are linear RGB.
+                let CIEXYZColor(r, g, b) = multiply_matrix_vector(&converter.matrix, &color.to_linear_with::<T>(&converter.source_white));
+                let LinearRGBColor(r, g, b) = gamut_map_linear_rgb(&LinearRGBColor(r, g, b), method);
+                To::from_linear_with::<T>(&CIEXYZColor(r, g, b), &converter.target_white).encode_u8()
+            },
+            None => converter.convert_with::<T>(&color).encode_u8()
+        };
+
+        pixel[0] = c0;
+        pixel[1] = c1;
+        pixel[2] = c2;
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ConversionPipeline {
+
+    /*
+    Whites are given as CIExyY. They are ignored for color spaces that are not relative to a white point,
+    but have to be valid nonetheless.
+    If accurate is true, the transfer functions are computed exactly instead of approximated,
+    which is slower but matches the f64 reference conversions.
+    */
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(
+        source_space: ColorSpaceId, source_white_x: f32, source_white_y: f32, source_white_yy: f32,
+        target_space: ColorSpaceId, target_white_x: f32, target_white_y: f32, target_white_yy: f32,
+        method: Option<ChromaticAdaptationMethod>, gamut_mapping: Option<GamutMappingMethod>, accurate: Option<bool>
+    ) -> Result<ConversionPipeline, ColorSpacesError> {
+        check_white_point(source_white_x, source_white_y, source_white_yy)?;
+        check_white_point(target_white_x, target_white_y, target_white_yy)?;
+
+        let source_white = CIEXYZColor(source_white_x / source_white_y * source_white_yy, source_white_yy, (1.0 - source_white_x - source_white_y) / source_white_y * source_white_yy);
+        let target_white = CIEXYZColor(target_white_x / target_white_y * target_white_yy, target_white_yy, (1.0 - target_white_x - target_white_y) / target_white_y * target_white_yy);
+        let method = method.unwrap_or_default();
+
+        let (source_white, target_white, matrix) = dispatch_color_spaces!(source_space, target_space, fused_conversion(&source_white, &target_white, method));
+
+        let gamut_mapping = match target_space {
+            ColorSpaceId::SRGB | ColorSpaceId::LinearRGB => Some(gamut_mapping.unwrap_or_default()),
+            _ => None
+        };
+
+        let accurate = accurate.unwrap_or(false);
+
+        Ok(ConversionPipeline { source_space, target_space, source_white, target_white, matrix, gamut_mapping, accurate })
+    }
+
+    pub fn convert_slice(&self, data: &mut [u8]) {
+        dispatch_color_spaces!(self.source_space, self.target_space, convert_pixels_u8(self, data));
+    }
+
+    // Converts the region of len bytes starting at offset.
+    #[cfg(feature = "std")]
+    pub fn convert_buffer(&self, buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+        self.convert_slice(buffer.region(offset, len)?);
+        Ok(())
+    }
+
+    /*
+    Same as convert_slice(), but the slice is split up between all threads of the thread pool.
+    Returns once the whole slice is converted.
+    */
+    #[cfg(feature = "parallel")]
+    pub fn convert_slice_parallel(&self, data: &mut [u8]) {
+        convert_parallel(data, &|chunk: &mut [u8]| self.convert_slice(chunk));
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn convert_buffer_parallel(&self, buffer: &mut ColorBuffer, offset: usize, len: usize) -> Result<(), ColorSpacesError> {
+        self.convert_slice_parallel(buffer.region(offset, len)?);
+        Ok(())
+    }
+
+    /*
+    Converts a rectangle of an image which starts at offset in the buffer and extends at most to
+    its end. See convert_rect() for how images and rectangles are given.
+    */
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "std")]
+    pub fn convert_buffer_rect(
+        &self, buffer: &mut ColorBuffer, offset: usize, width: usize, height: usize, stride: usize,
+        rect_x: usize, rect_y: usize, rect_width: usize, rect_height: usize
+    ) -> Result<(), ColorSpacesError> {
+        let len = buffer.len().saturating_sub(offset);
+        self.convert_rect(buffer.region(offset, len)?, width, height, stride, &ImageRect::new(rect_x, rect_y, rect_width, rect_height))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "parallel")]
+    pub fn convert_buffer_rect_parallel(
+        &self, buffer: &mut ColorBuffer, offset: usize, width: usize, height: usize, stride: usize,
+        rect_x: usize, rect_y: usize, rect_width: usize, rect_height: usize
+    ) -> Result<(), ColorSpacesError> {
+        let len = buffer.len().saturating_sub(offset);
+        self.convert_rect_parallel(buffer.region(offset, len)?, width, height, stride, &ImageRect::new(rect_x, rect_y, rect_width, rect_height))
+    }
+}
+
+impl ConversionPipeline {
+
+    // Converts the rectangle rect of an image, see crate::convert_rect().
+    pub fn convert_rect(&self, data: &mut [u8], width: usize, height: usize, stride: usize, rect: &ImageRect) -> Result<(), ColorSpacesError> {
+        convert_rect(data, width, height, stride, rect, |row| self.convert_slice(row))
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn convert_rect_parallel(&self, data: &mut [u8], width: usize, height: usize, stride: usize, rect: &ImageRect) -> Result<(), ColorSpacesError> {
+        convert_rect_in_parallel(data, width, height, stride, rect, |row| self.convert_slice(row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const D65_2: (f32, f32, f32) = (0.31271, 0.32902, 1.0);
+    const D50_2: (f32, f32, f32) = (0.34567, 0.35850, 1.0);
+
+    fn pipeline(source_space: ColorSpaceId, source_white: (f32, f32, f32), target_space: ColorSpaceId, target_white: (f32, f32, f32)) -> ConversionPipeline {
+        let (sx, sy, syy) = source_white;
+        let (tx, ty, tyy) = target_white;
+        ConversionPipeline::new(source_space, sx, sy, syy, target_space, tx, ty, tyy, None, None, None).unwrap()
+    }
+
+    fn assert_close(a: &[u8], b: &[u8], tolerance: u8) {
+        for (pixel_a, pixel_b) in a.chunks(4).zip(b.chunks(4)) {
+            for c in 0..4 {
+                assert!(pixel_a[c].abs_diff(pixel_b[c]) <= tolerance, "{:?} {:?}", pixel_a, pixel_b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipeline_matches_kernels() {
+        let (x, y, yy) = D50_2;
+        let original = srgb_test_pixels();
+
+        let mut expected = original.clone();
+        convert_srgb_to_lab(&mut expected, x, y, yy, None);
+        let mut data = original.clone();
+        pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELab, D50_2).convert_slice(&mut data);
+        assert_close(&data, &expected, 1);
+
+        let mut data = expected.clone();
+        convert_lab_to_srgb(&mut expected, x, y, yy, None, None);
+        pipeline(ColorSpaceId::CIELab, D50_2, ColorSpaceId::SRGB, D65_2).convert_slice(&mut data);
+        assert_close(&data, &expected, 1);
+
+        let mut expected = original.clone();
+        convert_srgb_to_oklch(&mut expected);
+        let mut data = original.clone();
+        pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::Oklch, D65_2).convert_slice(&mut data);
+        // The hue of grays is meaningless and depends on tiny rounding errors in the chroma.
+        for (pixel_data, pixel_expected) in data.chunks_mut(4).zip(expected.chunks_mut(4)) {
+            if pixel_data[1] == 0 && pixel_expected[1] == 0 {
+                pixel_data[2] = 0;
+                pixel_expected[2] = 0;
+            }
+        }
+        assert_close(&data, &expected, 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_pipeline_convert_buffer() {
+        let original = srgb_test_pixels();
+        let pipeline = pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELab, D50_2);
+
+        let mut expected = original.clone();
+        pipeline.convert_slice(&mut expected[8..]);
+        let mut buffer = ColorBuffer::new(original.len()).unwrap();
+        buffer.as_mut_slice().copy_from_slice(&original);
+        pipeline.convert_buffer(&mut buffer, 8, original.len() - 8).unwrap();
+        assert_eq!(buffer.as_slice(), &expected[..]);
+
+        assert!(pipeline.convert_buffer(&mut buffer, 8, original.len()).is_err());
+        assert!(pipeline.convert_buffer(&mut buffer, 2, 8).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_pipeline_convert_buffer_rect() {
+        // The test pixels as an image of 64 rows of 64 pixels, padded to 70 pixels, behind a header of 64 bytes.
+        let original = srgb_test_pixels();
+        let (width, height, stride, offset) = (64, 64, 70 * 4, 64);
+        let mut image = vec![7; offset + stride * height];
+        for (row, pixels) in original.chunks(width * 4).enumerate() {
+            image[offset + row * stride..][..width * 4].copy_from_slice(pixels);
+        }
+
+        let pipeline = pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELuv, D65_2);
+        let mut expected = image.clone();
+        for row in 2..42 {
+            pipeline.convert_slice(&mut expected[offset + row * stride + 3 * 4..][..50 * 4]);
+        }
+
+        let mut buffer = ColorBuffer::new(image.len()).unwrap();
+        buffer.as_mut_slice().copy_from_slice(&image);
+        pipeline.convert_buffer_rect(&mut buffer, offset, width, height, stride, 3, 2, 50, 40).unwrap();
+        assert_eq!(buffer.as_slice(), &expected[..]);
+
+        assert!(pipeline.convert_buffer_rect(&mut buffer, offset, width, height + 1, stride, 3, 2, 50, 40).is_err());
+        assert!(pipeline.convert_buffer_rect(&mut buffer, offset, width, height, stride, 30, 2, 50, 40).is_err());
+        assert!(pipeline.convert_buffer_rect(&mut buffer, image.len() + 4, 0, 0, 0, 0, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_errors() {
+        assert_eq!("CIELab".parse(), Ok(ColorSpaceId::CIELab));
+        assert_eq!("linear RGB".parse(), Ok(ColorSpaceId::LinearRGB));
+        assert_eq!("HSL".parse::<ColorSpaceId>(), Err(ColorSpacesError::UnsupportedSpace));
+
+        let (x, y, yy) = D65_2;
+        assert!(ConversionPipeline::new(ColorSpaceId::SRGB, x, 0.0, yy, ColorSpaceId::CIELab, x, y, yy, None, None, None).is_err());
+        assert!(ConversionPipeline::new(ColorSpaceId::SRGB, x, y, yy, ColorSpaceId::CIELab, x, y, 0.0, None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_without_srgb_pivot() {
+        // Lab to Luv directly must give the same result as going through sRGB, except for rounding.
+        let original = srgb_test_pixels();
+        let mut lab = original.clone();
+        pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELab, D50_2).convert_slice(&mut lab);
+
+        let mut direct = lab.clone();
+        pipeline(ColorSpaceId::CIELab, D50_2, ColorSpaceId::CIELuv, D65_2).convert_slice(&mut direct);
+
+        let mut pivot = original.clone();
+        pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELuv, D65_2).convert_slice(&mut pivot);
+
+        assert_close(&direct, &pivot, 2);
+    }
+
+    #[test]
+    fn test_pipeline_all_pairs() {
+        const SPACES: [ColorSpaceId; 9] = [
+            ColorSpaceId::SRGB, ColorSpaceId::LinearRGB, ColorSpaceId::CIEXYZ, ColorSpaceId::CIELab, ColorSpaceId::CIELCh,
+            ColorSpaceId::CIELuv, ColorSpaceId::CIELChuv, ColorSpaceId::Oklab, ColorSpaceId::Oklch
+        ];
+
+        // Colorful mid-range colors only, so that neither the steep parts of the transfer
+        // functions nor the undefined hues of grays get in the way.
+        let mut original = Vec::new();
+        for r in (68..=187).step_by(17) {
+            for g in (68..=187).step_by(17) {
+                for b in (68..=187).step_by(17) {
+                    if r.max(g).max(b) - r.min(g).min(b) >= 51 {
+                        original.extend_from_slice(&[r as u8, g as u8, b as u8, 5]);
+                    }
+                }
+            }
+        }
+
+        for &source in SPACES.iter() {
+            // The 8-bit XYZ encoding is the coarsest one, its errors get amplified by the matrix into RGB.
+            let tolerance = if source == ColorSpaceId::CIEXYZ { 6 } else { 3 };
+
+            let mut source_data = original.clone();
+            pipeline(ColorSpaceId::SRGB, D65_2, source, D50_2).convert_slice(&mut source_data);
+
+            for &target in SPACES.iter() {
+                let mut direct = source_data.clone();
+                pipeline(source, D50_2, target, D65_2).convert_slice(&mut direct);
+
+                let mut pivot = original.clone();
+                pipeline(ColorSpaceId::SRGB, D65_2, target, D65_2).convert_slice(&mut pivot);
+
+                if let ColorSpaceId::CIELCh | ColorSpaceId::CIELChuv | ColorSpaceId::Oklch = target {
+                    // Hues wrap around, so 255 and 0 are neighbors.
+                    for (pixel_direct, pixel_pivot) in direct.chunks_mut(4).zip(pivot.chunks_mut(4)) {
+                        if pixel_direct[2].abs_diff(pixel_pivot[2]) > 128 {
+                            pixel_direct[2] = pixel_direct[2].wrapping_add(128);
+                            pixel_pivot[2] = pixel_pivot[2].wrapping_add(128);
+                        }
+                    }
+                }
+                assert_close(&direct, &pivot, tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipeline_identity() {
+        let original = srgb_test_pixels();
+        let mut data = original.clone();
+        pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::SRGB, D65_2).convert_slice(&mut data);
+        assert_eq!(data, original);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_pipeline_parallel() {
+        // Enough pixels for several chunks, with a partial chunk at the end.
+        let mut original = Vec::new();
+        while original.len() < crate::thread_pool::CHUNK_PIXELS * 4 * 5 {
+            original.extend_from_slice(&srgb_test_pixels());
+        }
+
+        let pipeline = pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELab, D50_2);
+        let mut expected = original.clone();
+        pipeline.convert_slice(&mut expected);
+        let mut buffer = ColorBuffer::new(original.len()).unwrap();
+        buffer.as_mut_slice().copy_from_slice(&original);
+        pipeline.convert_buffer_parallel(&mut buffer, 0, original.len()).unwrap();
+        assert_eq!(buffer.as_slice(), &expected[..]);
+
+        // The same buffer as an image of 512 pixels wide rows, apart from a border of 16 pixels.
+        let (width, height) = (512, original.len() / 2048);
+        let mut expected = original.clone();
+        pipeline.convert_rect(&mut expected, width, height, 2048, &ImageRect::new(16, 16, width - 32, height - 32)).unwrap();
+        buffer.as_mut_slice().copy_from_slice(&original);
+        pipeline.convert_buffer_rect_parallel(&mut buffer, 0, width, height, 2048, 16, 16, width - 32, height - 32).unwrap();
+        assert_eq!(buffer.as_slice(), &expected[..]);
+    }
+
+    #[test]
+    fn test_pipeline_accurate() {
+        let (x, y, yy) = D65_2;
+        let accurate = ConversionPipeline::new(ColorSpaceId::SRGB, x, y, yy, ColorSpaceId::LinearRGB, x, y, yy, None, None, Some(true)).unwrap();
+
+        let mut data: Vec<u8> = (0..=255).flat_map(|v| vec![v, v, v, 255]).collect();
+        accurate.convert_slice(&mut data);
+        for (v, pixel) in data.chunks(4).enumerate() {
+            let expected = (srgb_digamma_f64(v as f64 / 255.0) * 255.0).round() as u8;
+            assert_eq!(pixel[0], expected);
+        }
+
+        // Exact and approximate transfer functions agree to within a step of the encoding.
+        let original = srgb_test_pixels();
+        let mut expected = original.clone();
+        pipeline(ColorSpaceId::SRGB, D65_2, ColorSpaceId::CIELab, D50_2).convert_slice(&mut expected);
+        let mut data = original.clone();
+        let (tx, ty, tyy) = D50_2;
+        ConversionPipeline::new(ColorSpaceId::SRGB, x, y, yy, ColorSpaceId::CIELab, tx, ty, tyy, None, None, Some(true)).unwrap().convert_slice(&mut data);
+        assert_close(&data, &expected, 1);
+    }
+}
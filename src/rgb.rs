@@ -1,53 +1,71 @@
 use crate::TristimulusColor;
+use crate::chromatic_adaptation::{Matrix3, multiply_matrix_vector};
+use crate::color_space::ColorSpace;
+use crate::transfer::{TransferFunctions, DefaultTransfer};
+use crate::gamut_mapping::{GamutMappingMethod, gamut_map_xyz_to_srgb};
+use core::f32::consts::{FRAC_1_SQRT_2, SQRT_2};
 
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-const CORRECTION_FACTORS_12TH_ROOT: [f32; 23] = [
-    0.5297315471796477,
-    0.5612310241546865,
-    0.5946035575013605, 
-    0.6299605249474366, 
-    0.6674199270850172, 
-    0.7071067811865476, 
-    0.7491535384383408, 
-    0.7937005259840998, 
-    0.8408964152537145, 
-    0.8908987181403393, 
-    0.9438743126816935, 
+pub(crate) const LINEAR_RGB_TO_XYZ: Matrix3 = [
+    [0.4123908, 0.35758433, 0.1804808],
+    [0.212639, 0.71516865, 0.07219232],
+    [0.019330818, 0.11919478, 0.95053214]
+];
+
+pub(crate) const XYZ_TO_LINEAR_RGB: Matrix3 = [
+    [3.24097, -1.5373832, -0.49861076],
+    [-0.96924365, 1.8759675, 0.04155506],
+    [0.05563008, -0.20397696, 1.0569715]
+];
+
+pub(crate) const CORRECTION_FACTORS_12TH_ROOT: [f32; 23] = [
+    0.5297316,
+    0.561231,
+    0.59460354, 
+    0.62996054, 
+    0.6674199, 
+    FRAC_1_SQRT_2, 
+    0.74915355, 
+    0.7937005, 
+    0.8408964, 
+    0.8908987, 
+    0.9438743, 
     1.0,
-    1.0594630943592953,
-    1.122462048309373,
-    1.189207115002721,
-    1.2599210498948732, 
-    1.3348398541700344, 
-    1.4142135623730951, 
-    1.4983070768766815, 
-    1.5874010519681994, 
-    1.681792830507429, 
-    1.7817974362806785, 
-    1.887748625363387
+    1.0594631,
+    1.122462,
+    1.1892071,
+    1.2599211, 
+    1.3348398, 
+    SQRT_2, 
+    1.4983071, 
+    1.587401, 
+    1.6817929, 
+    1.7817974, 
+    1.8877486
 ];
 
 const CORRECTION_FACTORS_5TH_ROOT: [f32; 9] = [
-    0.5743491774985174, 
-    0.6597539553864471, 
-    0.757858283255199, 
-    0.8705505632961241, 
+    0.57434916, 
+    0.659754, 
+    0.7578583, 
+    0.8705506, 
     1.0, 
-    1.148698354997035, 
-    1.3195079107728942, 
-    1.515716566510398, 
-    1.7411011265922482
+    1.1486983, 
+    1.319508, 
+    1.5157166, 
+    1.7411011
 ];
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Copy, Clone)]
 pub struct SRGBColor(pub f32, pub f32, pub f32);
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl SRGBColor {
 
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(r: f32, g: f32, b: f32) -> SRGBColor {
         SRGBColor(r, g, b)
     }
@@ -68,14 +86,49 @@ impl TristimulusColor for SRGBColor {
     }
 }
 
-#[wasm_bindgen]
+impl ColorSpace for SRGBColor {
+
+    const WHITE_RELATIVE: bool = false;
+
+    fn from_components(r: f32, g: f32, b: f32) -> SRGBColor {
+        SRGBColor(r, g, b)
+    }
+
+    fn to_linear(&self, white: &CIEXYZColor) -> CIEXYZColor {
+        self.to_linear_with::<DefaultTransfer>(white)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, white: &CIEXYZColor) -> SRGBColor {
+        SRGBColor::from_linear_with::<DefaultTransfer>(linear, white)
+    }
+
+    fn to_linear_with<T: TransferFunctions>(&self, _white: &CIEXYZColor) -> CIEXYZColor {
+        let &SRGBColor(r, g, b) = self;
+        CIEXYZColor(T::srgb_digamma(r), T::srgb_digamma(g), T::srgb_digamma(b))
+    }
+
+    fn from_linear_with<T: TransferFunctions>(linear: &CIEXYZColor, _white: &CIEXYZColor) -> SRGBColor {
+        let &CIEXYZColor(r_lin, g_lin, b_lin) = linear;
+        SRGBColor(T::srgb_gamma(r_lin), T::srgb_gamma(g_lin), T::srgb_gamma(b_lin))
+    }
+
+    fn linear_to_xyz_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        LINEAR_RGB_TO_XYZ
+    }
+
+    fn xyz_to_linear_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        XYZ_TO_LINEAR_RGB
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Copy, Clone)]
 pub struct LinearRGBColor(pub f32, pub f32, pub f32);
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl LinearRGBColor {
 
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(r_lin: f32, g_lin: f32, b_lin: f32) -> LinearRGBColor {
         LinearRGBColor(r_lin, g_lin, b_lin)
     }
@@ -96,14 +149,41 @@ impl TristimulusColor for LinearRGBColor {
     }
 }
 
-#[wasm_bindgen]
+impl ColorSpace for LinearRGBColor {
+
+    const WHITE_RELATIVE: bool = false;
+
+    fn from_components(r_lin: f32, g_lin: f32, b_lin: f32) -> LinearRGBColor {
+        LinearRGBColor(r_lin, g_lin, b_lin)
+    }
+
+    fn to_linear(&self, _white: &CIEXYZColor) -> CIEXYZColor {
+        let &LinearRGBColor(r_lin, g_lin, b_lin) = self;
+        CIEXYZColor(r_lin, g_lin, b_lin)
+    }
+
+    fn from_linear(linear: &CIEXYZColor, _white: &CIEXYZColor) -> LinearRGBColor {
+        let &CIEXYZColor(r_lin, g_lin, b_lin) = linear;
+        LinearRGBColor(r_lin, g_lin, b_lin)
+    }
+
+    fn linear_to_xyz_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        LINEAR_RGB_TO_XYZ
+    }
+
+    fn xyz_to_linear_matrix(_white: &CIEXYZColor) -> Matrix3 {
+        XYZ_TO_LINEAR_RGB
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Copy, Clone)]
 pub struct CIEXYZColor(pub f32, pub f32, pub f32);
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl CIEXYZColor {
 
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(x: f32, y: f32, z: f32) -> CIEXYZColor {
         CIEXYZColor(x, y, z)
     }
@@ -124,21 +204,48 @@ impl TristimulusColor for CIEXYZColor {
     }
 }
 
-#[wasm_bindgen]
+impl ColorSpace for CIEXYZColor {
+
+    const WHITE_RELATIVE: bool = false;
+
+    fn from_components(x: f32, y: f32, z: f32) -> CIEXYZColor {
+        CIEXYZColor(x, y, z)
+    }
+
+    fn to_linear(&self, _white: &CIEXYZColor) -> CIEXYZColor {
+        *self
+    }
+
+    fn from_linear(linear: &CIEXYZColor, _white: &CIEXYZColor) -> CIEXYZColor {
+        *linear
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn srgb_gamma(val: f32) -> f32 {
+    DefaultTransfer::srgb_gamma(val)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn srgb_digamma(val: f32) -> f32 {
+    DefaultTransfer::srgb_digamma(val)
+}
+
+#[inline(always)]
+pub(crate) fn srgb_gamma_approx(val: f32) -> f32 {
     if val <= 0.0031308 {
-        return val * 12.92;
+        val * 12.92
     } else {
         let float_bits: u32 = f32::to_bits(val);
         let exponent: i32 = ((float_bits >> 23) & 0xff) as i32 - 126;
         let new_float_bits: u32 = (float_bits & 0x807fffff) | 0x3f000000;
         let x: f32 = f32::from_bits(new_float_bits);
 
-        let y = ((((x * 0.06740218964272982 - 0.31414927092326544) * 
-                    x + 0.6193915145851007) *
-                        x - 0.6838657745157206) * 
-                            x + 0.5125530264346017) * 
-                                x + 0.798668892157733;
+        let y = ((((x * 0.06740219 - 0.31414926) * 
+                    x + 0.6193915) *
+                        x - 0.6838658) * 
+                            x + 0.51255304) * 
+                                x + 0.7986689;
         
         let exponent_new = exponent / 12;
         let correction_shift = f32::from_bits(((exponent_new as u32 + 127) & 0xff) << 23);
@@ -149,14 +256,15 @@ pub fn srgb_gamma(val: f32) -> f32 {
         let gamma_part = twelfth_root * twelfth_root;
         let gamma_part = gamma_part * gamma_part * twelfth_root;
 
-        return gamma_part * 1.055 - 0.055;
+        gamma_part * 1.055 - 0.055
     }
 }
 
-#[wasm_bindgen]
-pub fn srgb_digamma(val: f32) -> f32 {
+// const so that the linearization table in lut.rs can be built at compile time.
+#[inline(always)]
+pub(crate) const fn srgb_digamma_approx(val: f32) -> f32 {
     if val <= 0.04045 {
-        return val / 12.92;
+        val / 12.92
     } else {
         let digamma_part = (val + 0.055) / 1.055;
         let float_bits: u32 = f32::to_bits(digamma_part);
@@ -164,27 +272,25 @@ pub fn srgb_digamma(val: f32) -> f32 {
         let new_float_bits: u32 = (float_bits & 0x807fffff) | 0x3f000000;
         let x: f32 = f32::from_bits(new_float_bits);
 
-        let y = ((((x * 0.11857729282950705 - 0.5561006058653446) * 
-                    x + 1.108062007906361) *
-                        x - 1.2505477535604077) * 
-                            x + 1.0085571210525826) * 
-                                x + 0.5714529292453215;
+        let y = ((((x * 0.118577294 - 0.5561006) * 
+                    x + 1.108062) *
+                        x - 1.2505478) * 
+                            x + 1.0085571) * 
+                                x + 0.5714529;
         
         let exponent_new = exponent / 5;
-        let correction_shift = f32::from_bits(((exponent_new as i32 + 127) as u32 & 0xff) << 23);
+        let correction_shift = f32::from_bits(((exponent_new + 127) as u32 & 0xff) << 23);
         let correction_index = ((exponent - exponent_new * 5) + 4) as usize;
         let correction_factor = CORRECTION_FACTORS_5TH_ROOT[correction_index];
         let fifth_root = y * correction_shift * correction_factor;
 
         let digamma = fifth_root * fifth_root * fifth_root;
         let digamma = digamma * digamma;
-        let digamma = digamma * digamma;
-
-        return digamma;
+        digamma * digamma
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn srgb_to_linear_rgb(srgb: &SRGBColor) -> LinearRGBColor {
     let &SRGBColor(r, g, b) = srgb;
     LinearRGBColor(
@@ -194,7 +300,7 @@ pub fn srgb_to_linear_rgb(srgb: &SRGBColor) -> LinearRGBColor {
     )
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn linear_rgb_to_srgb(lin_rgb: &LinearRGBColor) -> SRGBColor {
     let &LinearRGBColor(lin_r, lin_g, lin_b) = lin_rgb;
     SRGBColor(
@@ -204,48 +310,32 @@ pub fn linear_rgb_to_srgb(lin_rgb: &LinearRGBColor) -> SRGBColor {
     )
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn srgb_to_xyz(rgb: &SRGBColor) -> CIEXYZColor {
     let &SRGBColor(r, g, b) = rgb;
-    let r_lin = srgb_digamma(r);
-    let g_lin = srgb_digamma(g);
-    let b_lin = srgb_digamma(b);
-    
-    CIEXYZColor(
-        r_lin * 0.4123907992659594812888840055 + g_lin * 0.3575843393838779637292839034 + b_lin * 0.1804807884018342875046284426,
-        r_lin * 0.2126390058715103575395808154 + g_lin * 0.7151686787677559274585678068 + b_lin * 0.07219231536073371500185137706,
-        r_lin * 0.01933081871559185068541643776 + g_lin * 0.1191947797946259879097613012 + b_lin * 0.9505321522496605808577097982
-    )
+    let lin_rgb = CIEXYZColor(srgb_digamma(r), srgb_digamma(g), srgb_digamma(b));
+    multiply_matrix_vector(&LINEAR_RGB_TO_XYZ, &lin_rgb)
 }
 
-#[wasm_bindgen]
+/*
+Colors outside of the sRGB gamut are mapped into it with the default GamutMappingMethod.
+Use gamut_map_xyz_to_srgb() to select a different method.
+*/
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn xyz_to_srgb(xyz: &CIEXYZColor) -> SRGBColor {
-    let &CIEXYZColor(x, y, z) = xyz;
-    SRGBColor(
-        srgb_gamma(x * 3.240969941904521343773680225 + y * -1.537383177570093457943925235 + z * -0.4986107602930032836574892651),
-        srgb_gamma(x * -0.9692436362808798261285146964 + y * 1.875967501507720667721122882 + z * 0.04155505740717561247596181202),
-        srgb_gamma(x * 0.05563007969699360845892843062 + y * -0.2039769588889765643494042455 + z * 1.056971514242878560719640180)
-    )
+    gamut_map_xyz_to_srgb(xyz, GamutMappingMethod::default())
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn linear_rgb_to_xyz(lin_rgb: &LinearRGBColor) -> CIEXYZColor {
     let &LinearRGBColor(r_lin, g_lin, b_lin) = lin_rgb;
-    CIEXYZColor(
-        r_lin * 0.4123907992659594812888840055 + g_lin * 0.3575843393838779637292839034 + b_lin * 0.1804807884018342875046284426,
-        r_lin * 0.2126390058715103575395808154 + g_lin * 0.7151686787677559274585678068 + b_lin * 0.07219231536073371500185137706,
-        r_lin * 0.01933081871559185068541643776 + g_lin * 0.1191947797946259879097613012 + b_lin * 0.9505321522496605808577097982
-    )
+    multiply_matrix_vector(&LINEAR_RGB_TO_XYZ, &CIEXYZColor(r_lin, g_lin, b_lin))
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn xyz_to_linear_rgb(xyz: &CIEXYZColor) -> LinearRGBColor {
-    let &CIEXYZColor(x, y, z) = xyz;
-    LinearRGBColor(
-        x * 3.240969941904521343773680225 + y * -1.537383177570093457943925235 + z * -0.4986107602930032836574892651,
-        x * -0.9692436362808798261285146964 + y * 1.875967501507720667721122882 + z * 0.04155505740717561247596181202,
-        x * 0.05563007969699360845892843062 + y * -0.2039769588889765643494042455 + z * 1.056971514242878560719640180
-    )
+    let CIEXYZColor(r_lin, g_lin, b_lin) = multiply_matrix_vector(&XYZ_TO_LINEAR_RGB, xyz);
+    LinearRGBColor(r_lin, g_lin, b_lin)
 }
 
 #[cfg(test)]
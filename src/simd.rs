@@ -0,0 +1,358 @@
+/*
+Four lane vectors for the SIMD kernels.
+
+On wasm32 with the simd128 target feature, these map directly onto v128 and the
+core::arch::wasm32 intrinsics. On every other target they are plain arrays, so the
+SIMD kernels can be compiled and tested natively and produce the same results.
+
+Only the operations the kernels need are provided. Min and max follow the pseudo-min/max
+semantics of wasm (and of f32::clamp for non-NaN values), integer arithmetic wraps
+like it does in the scalar code, which works on u32 bits and i32 exponents.
+*/
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use core::arch::wasm32::*;
+
+use core::ops::{Add, Sub, Mul, Div};
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[derive(Copy, Clone)]
+pub(crate) struct F32x4(v128);
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[derive(Copy, Clone)]
+pub(crate) struct U32x4(v128);
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+#[derive(Copy, Clone)]
+pub(crate) struct F32x4([f32; 4]);
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+#[derive(Copy, Clone)]
+pub(crate) struct U32x4([u32; 4]);
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl F32x4 {
+
+    #[inline(always)]
+    pub(crate) fn splat(val: f32) -> F32x4 {
+        F32x4(f32x4_splat(val))
+    }
+
+    #[inline(always)]
+    pub(crate) fn from_array(vals: [f32; 4]) -> F32x4 {
+        F32x4(f32x4(vals[0], vals[1], vals[2], vals[3]))
+    }
+
+    #[cfg_attr(not(any(test, feature = "gamma-lut")), allow(dead_code))]
+    #[inline(always)]
+    pub(crate) fn to_array(self) -> [f32; 4] {
+        [
+            f32x4_extract_lane::<0>(self.0),
+            f32x4_extract_lane::<1>(self.0),
+            f32x4_extract_lane::<2>(self.0),
+            f32x4_extract_lane::<3>(self.0)
+        ]
+    }
+
+    /*
+    Loads one channel of four RGBA pixels. The swizzle moves every fourth byte into the lowest
+    byte of a lane and zeroes the rest, since out of range indices produce zero.
+    */
+    #[inline(always)]
+    pub(crate) fn load_channel(pixels: &[u8], channel: usize) -> F32x4 {
+        assert!(pixels.len() >= 16 && channel < 4);
+        let bytes = unsafe { v128_load(pixels.as_ptr() as *const v128) };
+        let c = channel as i8;
+        let indices = i8x16(c, -1, -1, -1, c + 4, -1, -1, -1, c + 8, -1, -1, -1, c + 12, -1, -1, -1);
+        F32x4(f32x4_convert_u32x4(u8x16_swizzle(bytes, indices)))
+    }
+
+    #[inline(always)]
+    pub(crate) fn to_bits(self) -> U32x4 {
+        U32x4(self.0)
+    }
+
+    #[inline(always)]
+    pub(crate) fn from_bits(bits: U32x4) -> F32x4 {
+        F32x4(bits.0)
+    }
+
+    #[inline(always)]
+    pub(crate) fn clamp(self, min: f32, max: f32) -> F32x4 {
+        F32x4(f32x4_pmin(f32x4_pmax(self.0, f32x4_splat(min)), f32x4_splat(max)))
+    }
+
+    #[inline(always)]
+    pub(crate) fn le(self, other: f32) -> U32x4 {
+        U32x4(f32x4_le(self.0, f32x4_splat(other)))
+    }
+
+    #[inline(always)]
+    pub(crate) fn eq(self, other: f32) -> U32x4 {
+        U32x4(f32x4_eq(self.0, f32x4_splat(other)))
+    }
+
+    // Lanes of if_true where mask is set, otherwise lanes of if_false.
+    #[inline(always)]
+    pub(crate) fn select(mask: U32x4, if_true: F32x4, if_false: F32x4) -> F32x4 {
+        F32x4(v128_bitselect(if_true.0, if_false.0, mask.0))
+    }
+
+    #[inline(always)]
+    pub(crate) fn trunc_to_i32(self) -> U32x4 {
+        U32x4(i32x4_trunc_sat_f32x4(self.0))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl F32x4 {
+
+    #[inline(always)]
+    pub(crate) fn splat(val: f32) -> F32x4 {
+        F32x4([val; 4])
+    }
+
+    #[inline(always)]
+    pub(crate) fn from_array(vals: [f32; 4]) -> F32x4 {
+        F32x4(vals)
+    }
+
+    #[cfg_attr(not(any(test, feature = "gamma-lut")), allow(dead_code))]
+    #[inline(always)]
+    pub(crate) fn to_array(self) -> [f32; 4] {
+        self.0
+    }
+
+    #[inline(always)]
+    pub(crate) fn load_channel(pixels: &[u8], channel: usize) -> F32x4 {
+        F32x4([pixels[channel] as f32, pixels[channel + 4] as f32, pixels[channel + 8] as f32, pixels[channel + 12] as f32])
+    }
+
+    #[inline(always)]
+    pub(crate) fn to_bits(self) -> U32x4 {
+        U32x4(self.0.map(f32::to_bits))
+    }
+
+    #[inline(always)]
+    pub(crate) fn from_bits(bits: U32x4) -> F32x4 {
+        F32x4(bits.0.map(f32::from_bits))
+    }
+
+    #[inline(always)]
+    pub(crate) fn clamp(self, min: f32, max: f32) -> F32x4 {
+        F32x4(self.0.map(|val| {
+            let val = if val < min { min } else { val };
+            if max < val { max } else { val }
+        }))
+    }
+
+    #[inline(always)]
+    pub(crate) fn le(self, other: f32) -> U32x4 {
+        U32x4(self.0.map(|val| if val <= other { u32::MAX } else { 0 }))
+    }
+
+    #[inline(always)]
+    pub(crate) fn eq(self, other: f32) -> U32x4 {
+        U32x4(self.0.map(|val| if val == other { u32::MAX } else { 0 }))
+    }
+
+    #[inline(always)]
+    pub(crate) fn select(mask: U32x4, if_true: F32x4, if_false: F32x4) -> F32x4 {
+        let mut out = if_false.0;
+        for (i, val) in out.iter_mut().enumerate() {
+            if mask.0[i] != 0 {
+                *val = if_true.0[i];
+            }
+        }
+        F32x4(out)
+    }
+
+    #[inline(always)]
+    pub(crate) fn trunc_to_i32(self) -> U32x4 {
+        U32x4(self.0.map(|val| val as i32 as u32))
+    }
+}
+
+impl F32x4 {
+
+    // Looks up each lane in a table, there is no gather instruction in simd128.
+    #[inline(always)]
+    pub(crate) fn lookup(table: &[f32], indices: U32x4) -> F32x4 {
+        let [i0, i1, i2, i3] = indices.to_array();
+        F32x4::from_array([table[i0 as usize], table[i1 as usize], table[i2 as usize], table[i3 as usize]])
+    }
+
+    // Looks up one channel of four RGBA pixels.
+    #[inline(always)]
+    pub(crate) fn lookup_channel(table: &[f32], pixels: &[u8], channel: usize) -> F32x4 {
+        F32x4::from_array([
+            table[pixels[channel] as usize],
+            table[pixels[channel + 4] as usize],
+            table[pixels[channel + 8] as usize],
+            table[pixels[channel + 12] as usize]
+        ])
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl U32x4 {
+
+    #[inline(always)]
+    pub(crate) fn splat(val: u32) -> U32x4 {
+        U32x4(u32x4_splat(val))
+    }
+
+    #[inline(always)]
+    pub(crate) fn to_array(self) -> [u32; 4] {
+        [
+            u32x4_extract_lane::<0>(self.0),
+            u32x4_extract_lane::<1>(self.0),
+            u32x4_extract_lane::<2>(self.0),
+            u32x4_extract_lane::<3>(self.0)
+        ]
+    }
+
+    #[inline(always)]
+    pub(crate) fn and(self, other: U32x4) -> U32x4 {
+        U32x4(v128_and(self.0, other.0))
+    }
+
+    #[inline(always)]
+    pub(crate) fn or(self, other: U32x4) -> U32x4 {
+        U32x4(v128_or(self.0, other.0))
+    }
+
+    #[inline(always)]
+    pub(crate) fn shl(self, amount: u32) -> U32x4 {
+        U32x4(i32x4_shl(self.0, amount))
+    }
+
+    #[inline(always)]
+    pub(crate) fn shr(self, amount: u32) -> U32x4 {
+        U32x4(u32x4_shr(self.0, amount))
+    }
+
+    #[inline(always)]
+    pub(crate) fn wrapping_add(self, other: U32x4) -> U32x4 {
+        U32x4(i32x4_add(self.0, other.0))
+    }
+
+    #[inline(always)]
+    pub(crate) fn wrapping_sub(self, other: U32x4) -> U32x4 {
+        U32x4(i32x4_sub(self.0, other.0))
+    }
+
+    #[inline(always)]
+    pub(crate) fn wrapping_mul(self, other: U32x4) -> U32x4 {
+        U32x4(i32x4_mul(self.0, other.0))
+    }
+
+    // Interprets the lanes as i32.
+    #[inline(always)]
+    pub(crate) fn convert_i32_to_f32(self) -> F32x4 {
+        F32x4(f32x4_convert_i32x4(self.0))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl U32x4 {
+
+    #[inline(always)]
+    pub(crate) fn splat(val: u32) -> U32x4 {
+        U32x4([val; 4])
+    }
+
+    #[inline(always)]
+    pub(crate) fn to_array(self) -> [u32; 4] {
+        self.0
+    }
+
+    #[inline(always)]
+    fn zip(self, other: U32x4, op: fn(u32, u32) -> u32) -> U32x4 {
+        U32x4([op(self.0[0], other.0[0]), op(self.0[1], other.0[1]), op(self.0[2], other.0[2]), op(self.0[3], other.0[3])])
+    }
+
+    #[inline(always)]
+    pub(crate) fn and(self, other: U32x4) -> U32x4 {
+        self.zip(other, |a, b| a & b)
+    }
+
+    #[inline(always)]
+    pub(crate) fn or(self, other: U32x4) -> U32x4 {
+        self.zip(other, |a, b| a | b)
+    }
+
+    #[inline(always)]
+    pub(crate) fn shl(self, amount: u32) -> U32x4 {
+        U32x4(self.0.map(|val| val << amount))
+    }
+
+    #[inline(always)]
+    pub(crate) fn shr(self, amount: u32) -> U32x4 {
+        U32x4(self.0.map(|val| val >> amount))
+    }
+
+    #[inline(always)]
+    pub(crate) fn wrapping_add(self, other: U32x4) -> U32x4 {
+        self.zip(other, u32::wrapping_add)
+    }
+
+    #[inline(always)]
+    pub(crate) fn wrapping_sub(self, other: U32x4) -> U32x4 {
+        self.zip(other, u32::wrapping_sub)
+    }
+
+    #[inline(always)]
+    pub(crate) fn wrapping_mul(self, other: U32x4) -> U32x4 {
+        self.zip(other, u32::wrapping_mul)
+    }
+
+    #[inline(always)]
+    pub(crate) fn convert_i32_to_f32(self) -> F32x4 {
+        F32x4(self.0.map(|val| val as i32 as f32))
+    }
+}
+
+macro_rules! impl_f32x4_op {
+    ($trait:ident, $method:ident, $wasm_op:ident, $op:tt) => {
+        impl $trait for F32x4 {
+            type Output = F32x4;
+
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            #[inline(always)]
+            fn $method(self, other: F32x4) -> F32x4 {
+                F32x4($wasm_op(self.0, other.0))
+            }
+
+            #[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+            #[inline(always)]
+            fn $method(self, other: F32x4) -> F32x4 {
+                F32x4([self.0[0] $op other.0[0], self.0[1] $op other.0[1], self.0[2] $op other.0[2], self.0[3] $op other.0[3]])
+            }
+        }
+
+        impl $trait<f32> for F32x4 {
+            type Output = F32x4;
+
+            #[inline(always)]
+            fn $method(self, other: f32) -> F32x4 {
+                self $op F32x4::splat(other)
+            }
+        }
+
+        impl $trait<F32x4> for f32 {
+            type Output = F32x4;
+
+            #[inline(always)]
+            fn $method(self, other: F32x4) -> F32x4 {
+                F32x4::splat(self) $op other
+            }
+        }
+    };
+}
+
+impl_f32x4_op!(Add, add, f32x4_add, +);
+impl_f32x4_op!(Sub, sub, f32x4_sub, -);
+impl_f32x4_op!(Mul, mul, f32x4_mul, *);
+impl_f32x4_op!(Div, div, f32x4_div, /);
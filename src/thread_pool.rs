@@ -0,0 +1,385 @@
+/*
+Parallel conversion of large buffers, enabled with the "parallel" feature. Used by
+ConversionPipeline::convert_buffer_parallel(), and by convert_in_parallel() for any of the
+slice conversions. Rectangles of images are converted by convert_rect_in_parallel().
+
+A buffer is split into chunks of whole pixels, or of whole rows for rectangles, which are
+converted by the threads of a rayon pool. On native targets the pool is made of std threads and
+starts on first use, and the calling thread blocks until every chunk is converted. In the browser
+the threads are web workers that share the WASM memory, they are started by init_thread_pool()
+(see js/worker.js). Until then, or if the pool could not be started, buffers are converted on
+the calling thread alone. The main thread of a browser is not allowed to block, see
+convert_chunks_in() for how it waits for the workers.
+*/
+
+use std::sync::OnceLock;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use crate::error::ColorSpacesError;
+use crate::image::{ImageRect, rect_region};
+
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(target_arch = "wasm32")]
+use std::sync::Arc;
+#[cfg(target_arch = "wasm32")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use wasm_bindgen::prelude::*;
+
+// 64 KiB of RGBA pixels, a multiple of the 16 bytes converted at once by the SIMD kernels.
+pub(crate) const CHUNK_PIXELS: usize = 16384;
+
+static POOL: OnceLock<ThreadPool> = OnceLock::new();
+
+#[cfg(not(target_arch = "wasm32"))]
+fn thread_pool() -> Option<&'static ThreadPool> {
+    Some(POOL.get_or_init(|| {
+        ThreadPoolBuilder::new()
+            .thread_name(|i| format!("colorspaces-{}", i))
+            .build()
+            .expect("failed to start the conversion threads")
+    }))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn thread_pool() -> Option<&'static ThreadPool> {
+    POOL.get()
+}
+
+/*
+Starts the worker threads in the browser. spawn_worker is called for every thread with the
+WASM module, the shared memory and a thread handle, and has to start a web worker that
+instantiates the module with this memory and then calls run_pool_worker() with the handle.
+*/
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[wasm_bindgen]
+pub fn init_thread_pool(num_threads: usize, spawn_worker: &js_sys::Function) -> Result<(), JsValue> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .spawn_handler(|thread| {
+            let handle = Box::into_raw(Box::new(thread)) as usize;
+            spawn_worker.call3(&JsValue::NULL, &wasm_bindgen::module(), &wasm_bindgen::memory(), &JsValue::from(handle))
+                .map(drop)
+                .map_err(|_| std::io::Error::other("failed to start a web worker"))
+        })
+        .build()
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    POOL.set(pool).map_err(|_| JsValue::from_str("the thread pool is already running"))
+}
+
+/*
+Runs a thread of the pool on the calling web worker. Does not return until the pool shuts down.
+*/
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[wasm_bindgen]
+pub fn run_pool_worker(handle: usize) {
+    let thread = unsafe { Box::from_raw(handle as *mut rayon::ThreadBuilder) };
+    thread.run();
+}
+
+/*
+Applies convert to chunks of data on all threads of the pool and returns once all of data is converted.
+Every chunk consists of whole RGBA pixels.
+*/
+pub(crate) fn convert_parallel<T: Send + 'static>(data: &mut [T], convert: &(dyn Fn(&mut [T]) + Sync)) {
+    convert_chunks_parallel(data, CHUNK_PIXELS * 4, convert);
+}
+
+// Same as convert_parallel(), with chunks of chunk_len elements. Only the last chunk may be shorter.
+fn convert_chunks_parallel<T: Send + 'static>(data: &mut [T], chunk_len: usize, convert: &(dyn Fn(&mut [T]) + Sync)) {
+    match thread_pool() {
+        Some(pool) if data.len() > chunk_len => convert_chunks_in(pool, data, chunk_len, convert),
+        _ => convert(data)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn convert_chunks_in<T: Send + 'static>(pool: &ThreadPool, data: &mut [T], chunk_len: usize, convert: &(dyn Fn(&mut [T]) + Sync)) {
+    pool.install(|| data.par_chunks_mut(chunk_len).for_each(convert));
+}
+
+/*
+The main thread of a browser is not allowed to block (Atomics.wait throws there), so it can not
+wait for a rayon job like threads on native targets do. Instead the calling thread converts chunks
+itself until there are none left to claim, so it never waits for a chunk that no worker has
+started on. If the workers are busy with other work, or have not started yet, the calling thread
+ends up converting every chunk on its own. Afterwards it spins until the chunks the workers have
+claimed are finished, at most one per thread. This takes as long as the slowest of those workers
+needs for its chunk, which is not bounded if the browser suspends that worker in the middle of it.
+*/
+#[cfg(target_arch = "wasm32")]
+fn convert_chunks_in<T: Send + 'static>(pool: &ThreadPool, data: &mut [T], chunk_len: usize, convert: &(dyn Fn(&mut [T]) + Sync)) {
+    let num_chunks = data.len().div_ceil(chunk_len);
+    let shared = Arc::new(ChunkedBuffer {
+        ptr: data.as_mut_ptr(),
+        len: data.len(),
+        chunk_len,
+        num_chunks,
+        next_chunk: AtomicUsize::new(0),
+        finished_chunks: AtomicUsize::new(0),
+        panicked: AtomicBool::new(false),
+        // The lifetime is erased here, see ChunkedBuffer for why this is sound.
+        convert: unsafe { core::mem::transmute::<&(dyn Fn(&mut [T]) + Sync), &'static (dyn Fn(&mut [T]) + Sync)>(convert) }
+    });
+
+    for _ in 0..pool.current_num_threads().min(num_chunks - 1) {
+        let shared = Arc::clone(&shared);
+        // A panic is recorded by FinishChunk and raised again on the calling thread.
+        pool.spawn(move || drop(panic::catch_unwind(AssertUnwindSafe(|| shared.work()))));
+    }
+
+    /*
+    Even if a chunk panics here, the workers have to finish before data is released.
+    Every chunk has been claimed once work() returns, so only the chunks the workers are
+    still converting are waited for.
+    */
+    let result = panic::catch_unwind(AssertUnwindSafe(|| shared.work()));
+    while shared.finished_chunks.load(Ordering::Acquire) < num_chunks {
+        core::hint::spin_loop();
+    }
+
+    if let Err(payload) = result {
+        panic::resume_unwind(payload);
+    }
+    if shared.panicked.load(Ordering::Relaxed) {
+        panic!("a conversion thread panicked");
+    }
+}
+
+/*
+A buffer shared by all threads of a conversion in the browser. The buffer and the conversion
+function are borrowed from convert_chunks_in(), which does not return before every chunk is
+finished. Workers that start later than that see no chunks left and never touch either of them.
+*/
+#[cfg(target_arch = "wasm32")]
+struct ChunkedBuffer<T: 'static> {
+    ptr: *mut T,
+    len: usize,
+    chunk_len: usize,
+    num_chunks: usize,
+    next_chunk: AtomicUsize,
+    finished_chunks: AtomicUsize,
+    panicked: AtomicBool,
+    convert: *const (dyn Fn(&mut [T]) + Sync)
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe impl<T: Send> Send for ChunkedBuffer<T> {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl<T: Send> Sync for ChunkedBuffer<T> {}
+
+// Marks a chunk as finished, even if its conversion panics, so the calling thread does not wait forever.
+#[cfg(target_arch = "wasm32")]
+struct FinishChunk<'a, T: 'static>(&'a ChunkedBuffer<T>);
+
+#[cfg(target_arch = "wasm32")]
+impl<T> Drop for FinishChunk<'_, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.panicked.store(true, Ordering::Relaxed);
+        }
+        self.0.finished_chunks.fetch_add(1, Ordering::Release);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: Send> ChunkedBuffer<T> {
+
+    // Converts chunks until there are none left to claim.
+    fn work(&self) {
+        loop {
+            let chunk = self.next_chunk.fetch_add(1, Ordering::Relaxed);
+            if chunk >= self.num_chunks {
+                return;
+            }
+
+            let _finish = FinishChunk(self);
+            let start = chunk * self.chunk_len;
+            let len = self.chunk_len.min(self.len - start);
+            unsafe {
+                // Every chunk is claimed exactly once, so no two threads see the same bytes.
+                (*self.convert)(core::slice::from_raw_parts_mut(self.ptr.add(start), len));
+            }
+        }
+    }
+}
+
+/*
+Runs a slice conversion on all cores, for example:
+
+    convert_in_parallel(&mut frame, |pixels| convert_srgb_to_lab(pixels, 0.34567, 0.35850, 1.0, None));
+
+The result is the same as calling the conversion on the whole slice.
+*/
+pub fn convert_in_parallel<T, F>(data: &mut [T], convert: F)
+    where T: Send + 'static, F: Fn(&mut [T]) + Sync
+{
+    convert_parallel(data, &convert);
+}
+
+/*
+Same as convert_rect(), but the rows of the rectangle are split up between all threads of the pool.
+*/
+pub fn convert_rect_in_parallel<F>(data: &mut [u8], width: usize, height: usize, stride: usize, rect: &ImageRect, convert: F) -> Result<(), ColorSpacesError>
+    where F: Fn(&mut [u8]) + Sync
+{
+    let (region, row_len) = rect_region(data, width, height, stride, rect)?;
+    if row_len == 0 {
+        return Ok(());
+    }
+
+    // Chunks start at a row of the rectangle and hold about as many pixels as the chunks of contiguous buffers.
+    let rows_per_chunk = (CHUNK_PIXELS * 4 / row_len).max(1);
+    convert_chunks_parallel(region, rows_per_chunk * stride, &|chunk: &mut [u8]| {
+        for row in chunk.chunks_mut(stride) {
+            convert(&mut row[..row_len]);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[test]
+    fn test_convert_parallel_every_byte_once() {
+        let len = CHUNK_PIXELS * 4 * 7 + 12;
+        let mut data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        convert_parallel(&mut data, &|chunk: &mut [u8]| {
+            assert_eq!(chunk.len() % 4, 0);
+            for v in chunk.iter_mut() {
+                *v = v.wrapping_add(1);
+            }
+        });
+
+        for (i, &v) in data.iter().enumerate() {
+            assert_eq!(v, (i % 251) as u8 + 1);
+        }
+    }
+
+    #[test]
+    fn test_convert_parallel_small_buffer() {
+        let mut data = vec![1u8; 64];
+        let calls = AtomicUsize::new(0);
+        convert_parallel(&mut data, &|chunk: &mut [u8]| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            chunk.fill(2);
+        });
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert_eq!(data, vec![2u8; 64]);
+    }
+
+    // A frame of several chunks and a partial one, filled with the shared test pixels over and over.
+    fn test_frame() -> Vec<u8> {
+        crate::srgb_test_pixels().into_iter().cycle().take((CHUNK_PIXELS * 5 + 1000) * 4).collect()
+    }
+
+    fn assert_parallel_matches_serial<T, F>(original: &[T], convert: F)
+        where T: Send + Copy + PartialEq + core::fmt::Debug + 'static, F: Fn(&mut [T]) + Sync
+    {
+        let mut serial = original.to_vec();
+        convert(&mut serial);
+        let mut parallel = original.to_vec();
+        convert_in_parallel(&mut parallel, &convert);
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn test_u8_conversions_in_parallel() {
+        let (x, y, yy) = (0.34567, 0.35850, 1.0);
+        let frame = test_frame();
+        assert_parallel_matches_serial(&frame, |data: &mut [u8]| crate::convert_srgb_to_xyz(data));
+        assert_parallel_matches_serial(&frame, |data: &mut [u8]| crate::convert_srgb_to_lab(data, x, y, yy, None));
+        assert_parallel_matches_serial(&frame, |data: &mut [u8]| crate::convert_lab_to_srgb(data, x, y, yy, None, None));
+        assert_parallel_matches_serial(&frame, |data: &mut [u8]| crate::convert_srgb_to_luv(data, x, y, yy, None));
+        assert_parallel_matches_serial(&frame, |data: &mut [u8]| crate::convert_srgb_to_oklch(data));
+        assert_parallel_matches_serial(&frame, |data: &mut [u8]| crate::convert_oklch_to_srgb(data, Some(crate::GamutMappingMethod::OklchChromaReduction)));
+    }
+
+    #[test]
+    fn test_f32_conversions_in_parallel() {
+        let (x, y, yy) = (0.34567, 0.35850, 1.0);
+        let frame: Vec<f32> = test_frame().iter().map(|&v| v as f32 / 255.0).collect();
+        assert_parallel_matches_serial(&frame, |data: &mut [f32]| crate::convert_f32_srgb_to_lab(data, x, y, yy, None));
+        assert_parallel_matches_serial(&frame, |data: &mut [f32]| crate::convert_f32_srgb_to_lchuv(data, x, y, yy, None));
+        assert_parallel_matches_serial(&frame, |data: &mut [f32]| crate::convert_f32_srgb_to_oklab(data));
+
+        let mut lab = frame.clone();
+        crate::convert_f32_srgb_to_lab(&mut lab, x, y, yy, None);
+        assert_parallel_matches_serial(&lab, |data: &mut [f32]| crate::convert_f32_lab_to_srgb(data, x, y, yy, None, None));
+    }
+
+    #[test]
+    fn test_convert_rect_in_parallel() {
+        // A frame of 1000 pixels wide rows with 24 pixels of padding, converted apart from a border of 10 pixels.
+        let frame = test_frame();
+        let stride = 1024 * 4;
+        let (width, height) = (1000, frame.len() / stride);
+        let rect = ImageRect::new(10, 10, width - 20, height - 20);
+        let convert = |data: &mut [u8]| crate::convert_srgb_to_lab(data, 0.34567, 0.35850, 1.0, None);
+
+        let mut serial = frame.clone();
+        crate::convert_rect(&mut serial, width, height, stride, &rect, convert).unwrap();
+        let mut parallel = frame.clone();
+        convert_rect_in_parallel(&mut parallel, width, height, stride, &rect, convert).unwrap();
+        assert_eq!(serial, parallel);
+
+        assert_eq!(&parallel[..stride * 10], &frame[..stride * 10]);
+        assert_ne!(&parallel[stride * 10 + 40..stride * 11], &frame[stride * 10 + 40..stride * 11]);
+
+        assert!(convert_rect_in_parallel(&mut parallel, width, height + 2, stride, &rect, convert).is_err());
+    }
+
+    #[test]
+    fn test_convert_parallel_busy_pool() {
+        // Occupy every thread of a separate pool until some time after the conversion has started.
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let release = Arc::new(AtomicBool::new(false));
+        for _ in 0..pool.current_num_threads() {
+            let release = Arc::clone(&release);
+            pool.spawn(move || {
+                while !release.load(Ordering::Acquire) {
+                    std::thread::yield_now();
+                }
+            });
+        }
+        let releaser = {
+            let release = Arc::clone(&release);
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                release.store(true, Ordering::Release);
+            })
+        };
+
+        // The conversion waits for the pool instead of failing or skipping chunks.
+        let mut data = vec![0u8; CHUNK_PIXELS * 4 * 6];
+        convert_chunks_in(&pool, &mut data, CHUNK_PIXELS * 4, &|chunk: &mut [u8]| chunk.fill(1));
+        releaser.join().unwrap();
+        assert!(data.iter().all(|&v| v == 1));
+
+        // Conversions started from a thread of the pool run on the pool as well.
+        pool.install(|| convert_chunks_in(&pool, &mut data, CHUNK_PIXELS * 4, &|chunk: &mut [u8]| chunk.fill(2)));
+        assert!(data.iter().all(|&v| v == 2));
+    }
+
+    // Depending on which thread gets the failing chunk, either panic reaches the caller.
+    #[test]
+    #[should_panic]
+    fn test_convert_parallel_panic() {
+        let mut data = vec![0u8; CHUNK_PIXELS * 4 * 8];
+        let start = data.as_ptr() as usize;
+        convert_parallel(&mut data, &|chunk: &mut [u8]| {
+            if (chunk.as_ptr() as usize - start) / (CHUNK_PIXELS * 4) == 5 {
+                panic!("chunk failed");
+            }
+        });
+    }
+}
@@ -0,0 +1,243 @@
+use crate::rgb::{srgb_gamma_approx, srgb_digamma_approx};
+use crate::cielab::lab_gamma_approx;
+use crate::cieluv::luv_gamma_approx;
+use crate::oklab::oklab_gamma_approx;
+use crate::{srgb_gamma_f64, srgb_digamma_f64, lab_gamma_f64};
+#[cfg(all(not(feature = "std"), not(test)))]
+use num_traits::Float;
+
+/*
+The non-linear parts of the color spaces that are expensive to compute exactly.
+
+Approximate uses polynomial approximations of the roots, which are considerably faster
+than powf(). Exact computes the functions in double precision and rounds the result,
+which gives reference results at the cost of speed.
+
+The free conversion functions and the convert_memory_* kernels use DefaultTransfer, which is
+Exact when the crate is built with the "accurate" feature. ConversionPipeline can also select
+the transfer functions at runtime.
+*/
+pub trait TransferFunctions {
+
+    fn srgb_gamma(val: f32) -> f32;
+
+    fn srgb_digamma(val: f32) -> f32;
+
+    fn lab_gamma(val: f32) -> f32;
+
+    // Cube root of relative luminance, the linear segment is handled by the caller.
+    fn luv_gamma(val: f32) -> f32;
+
+    fn oklab_gamma(val: f32) -> f32;
+}
+
+/*
+Maximum errors over all f32 in [0, 1], measured against the correctly rounded results
+of Exact (see test_approximate_error_exhaustive):
+
+    srgb_gamma      109 ulp, 3.9e-6 absolute
+    srgb_digamma    265 ulp, 1.4e-5 absolute
+    lab_gamma        21 ulp, 1.5e-6 absolute
+    luv_gamma        21 ulp, 1.5e-6 absolute (above the linear segment of CIELuv)
+    oklab_gamma      21 ulp, 1.5e-6 absolute (normal floats, same for negative values)
+
+Subnormal inputs to oklab_gamma are only accurate to the absolute bound.
+*/
+#[derive(Debug, Copy, Clone)]
+pub struct Approximate;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Exact;
+
+#[cfg(not(feature = "accurate"))]
+pub type DefaultTransfer = Approximate;
+
+#[cfg(feature = "accurate")]
+pub type DefaultTransfer = Exact;
+
+impl TransferFunctions for Approximate {
+
+    #[inline(always)]
+    fn srgb_gamma(val: f32) -> f32 {
+        srgb_gamma_approx(val)
+    }
+
+    #[inline(always)]
+    fn srgb_digamma(val: f32) -> f32 {
+        srgb_digamma_approx(val)
+    }
+
+    #[inline(always)]
+    fn lab_gamma(val: f32) -> f32 {
+        lab_gamma_approx(val)
+    }
+
+    #[inline(always)]
+    fn luv_gamma(val: f32) -> f32 {
+        luv_gamma_approx(val)
+    }
+
+    #[inline(always)]
+    fn oklab_gamma(val: f32) -> f32 {
+        oklab_gamma_approx(val)
+    }
+}
+
+impl TransferFunctions for Exact {
+
+    #[inline(always)]
+    fn srgb_gamma(val: f32) -> f32 {
+        srgb_gamma_f64(val as f64) as f32
+    }
+
+    #[inline(always)]
+    fn srgb_digamma(val: f32) -> f32 {
+        srgb_digamma_f64(val as f64) as f32
+    }
+
+    #[inline(always)]
+    fn lab_gamma(val: f32) -> f32 {
+        lab_gamma_f64(val as f64) as f32
+    }
+
+    #[inline(always)]
+    fn luv_gamma(val: f32) -> f32 {
+        (val as f64).cbrt() as f32
+    }
+
+    #[inline(always)]
+    fn oklab_gamma(val: f32) -> f32 {
+        (val as f64).cbrt() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_exact_matches_powf() {
+        assert_eq!(Exact::srgb_gamma(0.5), (1.055 * 0.5f64.powf(1.0 / 2.4) - 0.055) as f32);
+        assert_eq!(Exact::srgb_digamma(0.5), ((0.5f64 + 0.055) / 1.055).powf(2.4) as f32);
+        assert_eq!(Exact::lab_gamma(0.5), 0.5f64.cbrt() as f32);
+        assert_eq!(Exact::luv_gamma(0.125), 0.5);
+        assert_eq!(Exact::oklab_gamma(-0.125), -0.5);
+    }
+
+    struct ErrorBound {
+        ulp: u32,
+        absolute: f64
+    }
+
+    const SRGB_GAMMA_BOUND: ErrorBound = ErrorBound { ulp: 109, absolute: 3.9e-6 };
+    const SRGB_DIGAMMA_BOUND: ErrorBound = ErrorBound { ulp: 265, absolute: 1.4e-5 };
+    const CUBE_ROOT_BOUND: ErrorBound = ErrorBound { ulp: 21, absolute: 1.5e-6 };
+
+    // Start of the cube root segment of CIELuv, see xyz_to_luv_precomputed_white().
+    const LUV_THRESHOLD: f32 = 0.008856452;
+
+    fn ulp_distance(a: f32, b: f32) -> u32 {
+        (a.to_bits() as i64 - b.to_bits() as i64).unsigned_abs() as u32
+    }
+
+    /*
+    Checks every f32 in [start, 1] whose bit pattern is a multiple of step.
+    The approximation is compared with the correctly rounded reference for the ulp bound
+    and with the unrounded reference for the absolute bound.
+    */
+    fn assert_error_bound(approx: fn(f32) -> f32, reference: fn(f64) -> f64, start: f32, step: u32, bound: &ErrorBound) {
+        let mut bits = start.to_bits();
+        while bits <= 1.0f32.to_bits() {
+            let val = f32::from_bits(bits);
+            let approx_val = approx(val);
+            let reference_val = reference(val as f64);
+            assert!(ulp_distance(approx_val, reference_val as f32) <= bound.ulp, "{} {} {}", val, approx_val, reference_val);
+            assert!((approx_val as f64 - reference_val).abs() <= bound.absolute, "{} {} {}", val, approx_val, reference_val);
+            bits += step;
+        }
+    }
+
+    fn cube_root(val: f64) -> f64 {
+        val.cbrt()
+    }
+
+    fn negative_cube_root(val: f64) -> f64 {
+        -val.cbrt()
+    }
+
+    fn assert_error_bounds(step: u32) {
+        assert_error_bound(Approximate::srgb_gamma, srgb_gamma_f64, 0.0, step, &SRGB_GAMMA_BOUND);
+        assert_error_bound(Approximate::srgb_digamma, srgb_digamma_f64, 0.0, step, &SRGB_DIGAMMA_BOUND);
+        assert_error_bound(Approximate::lab_gamma, lab_gamma_f64, 0.0, step, &CUBE_ROOT_BOUND);
+        assert_error_bound(Approximate::luv_gamma, cube_root, LUV_THRESHOLD, step, &CUBE_ROOT_BOUND);
+        assert_error_bound(Approximate::oklab_gamma, cube_root, f32::MIN_POSITIVE, step, &CUBE_ROOT_BOUND);
+        assert_error_bound(|val| Approximate::oklab_gamma(-val), negative_cube_root, f32::MIN_POSITIVE, step, &CUBE_ROOT_BOUND);
+    }
+
+    #[test]
+    fn test_approximate_error_dense() {
+        // Every 257th float. The step is odd, so the sweep does not keep hitting the same mantissas.
+        assert_error_bounds(257);
+
+        // The correction tables switch at powers of two.
+        for exponent in -126..=0 {
+            let val = 2.0f32.powi(exponent);
+            for &val in &[f32::from_bits(val.to_bits() - 1), val, f32::from_bits(val.to_bits() + 1)] {
+                assert!((Approximate::oklab_gamma(val) as f64 - (val as f64).cbrt()).abs() <= CUBE_ROOT_BOUND.absolute);
+                assert!((Approximate::srgb_gamma(val) as f64 - srgb_gamma_f64(val as f64)).abs() <= SRGB_GAMMA_BOUND.absolute);
+                assert!((Approximate::srgb_digamma(val) as f64 - srgb_digamma_f64(val as f64)).abs() <= SRGB_DIGAMMA_BOUND.absolute);
+            }
+        }
+    }
+
+    // Takes a minute or two in release mode: cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn test_approximate_error_exhaustive() {
+        assert_error_bounds(1);
+    }
+
+    #[test]
+    fn test_oklab_gamma_subnormal() {
+        let mut bits = 1;
+        while bits < f32::MIN_POSITIVE.to_bits() {
+            let val = f32::from_bits(bits);
+            assert!((Approximate::oklab_gamma(val) as f64 - (val as f64).cbrt()).abs() <= CUBE_ROOT_BOUND.absolute);
+            bits += 4099;
+        }
+    }
+
+    /*
+    Every 8-bit sRGB value survives the trip through linear RGB as long as the linear value
+    stays in floating point. Storing it in 8 bits in between, as convert_srgb_to_linear_rgb()
+    does, cannot be lossless: the 256 linear steps are far too coarse for the dark sRGB values.
+    */
+    #[test]
+    fn test_srgb_u8_round_trip() {
+        for v in 0..=255u8 {
+            let srgb = v as f32 / 255.0;
+            assert_eq!((Approximate::srgb_gamma(Approximate::srgb_digamma(srgb)) * 255.0).round() as u8, v);
+            assert_eq!((Exact::srgb_gamma(Exact::srgb_digamma(srgb)) * 255.0).round() as u8, v);
+        }
+
+        // The u8 kernels are still consistent: every linear value they produce is reproduced exactly.
+        let mut linear: Vec<u8> = (0..=255).flat_map(|v| vec![v, v, v, 255]).collect();
+        crate::convert_srgb_to_linear_rgb(&mut linear);
+        let mut data = linear.clone();
+        crate::convert_linear_rgb_to_srgb(&mut data);
+        crate::convert_srgb_to_linear_rgb(&mut data);
+        assert_eq!(data, linear);
+    }
+
+    #[test]
+    fn test_approximate_close_to_exact() {
+        for i in 0..=1000 {
+            let val = i as f32 / 1000.0;
+            assert!((Approximate::srgb_gamma(val) - Exact::srgb_gamma(val)).abs() < 1.0e-4);
+            assert!((Approximate::srgb_digamma(val) - Exact::srgb_digamma(val)).abs() < 1.0e-4);
+            assert!((Approximate::lab_gamma(val) - Exact::lab_gamma(val)).abs() < 1.0e-4);
+            assert!((Approximate::oklab_gamma(val - 0.5) - Exact::oklab_gamma(val - 0.5)).abs() < 1.0e-4);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+/*
+Tests of the interface the JavaScript side of the library relies on. They go through the
+generated bindings instead of calling the Rust functions directly, so a function that is no
+longer exported fails here. Run by the test script in package.json.
+*/
+
+use colorspaces::*;
+use js_sys::{Array, Function, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::*;
+
+fn call_method(object: &JsValue, name: &str, args: &Array) -> JsValue {
+    let method: Function = Reflect::get(object, &JsValue::from_str(name))
+        .unwrap()
+        .dyn_into()
+        .unwrap_or_else(|_| panic!("{} is not exported", name));
+    method.apply(object, args).unwrap()
+}
+
+// The components of a color object, read like js/colorUtils.js does.
+fn components(color: &JsValue) -> [f32; 3] {
+    [0, 1, 2].map(|i| Reflect::get(color, &JsValue::from(i)).unwrap().as_f64().unwrap() as f32)
+}
+
+const D65: CIEXYZColor = CIEXYZColor(0.95047, 1.0, 1.08883);
+const D50: CIEXYZColor = CIEXYZColor(0.96422, 1.0, 0.82521);
+
+// adaptXYZ() in js/colorUtils.js
+#[wasm_bindgen_test]
+fn test_chromatic_adaptation_apply() {
+    let adaptation = JsValue::from(ChromaticAdaptation::new(&D65, &D50, ChromaticAdaptationMethod::Bradford));
+    let [x, y, z] = components(&call_method(&adaptation, "apply", &Array::of1(&JsValue::from(D65))));
+    assert!((x - D50.0).abs() < 1.0e-3 && (y - D50.1).abs() < 1.0e-3 && (z - D50.2).abs() < 1.0e-3);
+}
+
+// A CIELab color relative to D50 converted to sRGB, the way colorSpaceToXYZ() and XYZToColorSpace() do it.
+#[wasm_bindgen_test]
+fn test_white_relative_conversion() {
+    let xyz = lab_to_xyz(&CIELabColor(100.0, 0.0, 0.0), &D50);
+    let adaptation = JsValue::from(ChromaticAdaptation::new(&D50, &SRGB_WHITE, ChromaticAdaptationMethod::Bradford));
+    let adapted = call_method(&adaptation, "apply", &Array::of1(&JsValue::from(xyz)));
+    let [x, y, z] = components(&adapted);
+
+    let SRGBColor(r, g, b) = xyz_to_srgb(&CIEXYZColor(x, y, z));
+    assert!((r - 1.0).abs() < 1.0e-3 && (g - 1.0).abs() < 1.0e-3 && (b - 1.0).abs() < 1.0e-3);
+}
@@ -0,0 +1,8 @@
+/*
+The WASM module loaded by js/main.js and js/worker.js.
+
+Everything exported to JavaScript is declared in the colorspaces crate. It is kept in its own
+crate because a cdylib needs a panic handler, which would prevent building colorspaces without std.
+*/
+
+pub use colorspaces::*;